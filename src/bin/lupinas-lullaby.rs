@@ -58,6 +58,152 @@ Possibly only relevant in languages with structural typing e.g. TypeScript."#)
                 .required(false)
                 .takes_value(false),
         )
+        .arg(
+            clap::Arg::with_name("allow-additional-properties")
+                .long("allow-additional-properties")
+                .help(r#"Objects accept unknown properties unless their schema has `additionalProperties: false`.
+This adds an index signature of `unknown` to every other object, making the types more permissive."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("sort-properties")
+                .long("sort-properties")
+                .help(r#"Sort the properties of objects by name instead of keeping the order of the specification."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("indent")
+                .long("indent")
+                .help(r#"The indentation of one level, either a number of spaces or `tab`.
+Defaults to 2 spaces."#)
+                .required(false)
+                .takes_value(true)
+                .validator(|v| {
+                    if v == "tab" || v.parse::<usize>().is_ok() {
+                        Ok(())
+                    } else {
+                        Err("expected a number of spaces or 'tab'".to_string())
+                    }
+                }),
+        )
+        .arg(
+            clap::Arg::with_name("quote")
+                .long("quote")
+                .help("The quotes around string literals and property names. Defaults to double quotes.")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["single", "double"]),
+        )
+        .arg(
+            clap::Arg::with_name("no-semicolons")
+                .long("no-semicolons")
+                .help("Don't terminate types and properties with semicolons.")
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("line-width")
+                .long("line-width")
+                .help(r#"Unions longer than this are split with one variant per line.
+Defaults to 80."#)
+                .required(false)
+                .takes_value(true)
+                .validator(|v| {
+                    v.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| "expected a number".to_string())
+                }),
+        )
+        .arg(
+            clap::Arg::with_name("format-mapping")
+                .long("format-mapping")
+                .help(r#"Generate the schemas of a type and a format as another type, written as `type:format=target`,
+e.g. `string:date-time=string`, `integer:int64=bigint` or `string:binary=Blob`.
+Dates are `Date` unless overridden.
+Takes multiple occurences."#)
+                .required(false)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|v| {
+                    openapi::FormatMappings::parse(&v)
+                        .map(|_| ())
+                        .ok_or_else(|| "expected `type:format=target`".to_string())
+                }),
+        )
+        .arg(
+            clap::Arg::with_name("interfaces")
+                .long("interfaces")
+                .help(r#"Declare objects as interfaces instead of type aliases.
+An `allOf` of references to objects becomes an interface extending them."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("enums")
+                .long("enums")
+                .help(r#"How string enums are declared: a union of literals, an `enum` or a `const` array with a type of its elements.
+Enum members are named by `x-enum-varnames` or `x-enumNames`, or after their values.
+Defaults to union."#)
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["union", "enum", "const"]),
+        )
+        .arg(
+            clap::Arg::with_name("hoist-nested-types")
+                .long("hoist-nested-types")
+                .help(r#"Declare the objects and string enums nested in properties as types of their own.
+They are named after their `title`, or after their parent and the property e.g. `OrderStatus`."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("skip-paths")
+                .long("skip-paths")
+                .help(r#"Only generate the types of the definitions.
+By default, the parameters, request body and responses of every operation get types too e.g. `ListPetsQueryParams`."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("client")
+                .long("client")
+                .help(r#"Generate a `createClient` function with one method per operation, sending requests with `fetch`.
+Its options set the base URL and intercept the requests."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("branded-types")
+                .long("branded-types")
+                .help(r#"Generate branded types for schemas with an `x-brand`,
+and for strings formatted as `uuid`, `email`, `uri` or `date`,
+e.g. `Uuid` declared once as `string & { readonly __brand: "Uuid" }`.
+Only applies to the TypeScript targets."#)
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            clap::Arg::with_name("target")
+                .long("target")
+                .help(r#"What the definitions are generated as: TypeScript types, zod schemas with their types inferred from them,
+Rust serde types, Python `TypedDict` classes or pydantic models, or Go structs.
+Rust types need the `serde` crate, and `serde_json` for the ones Rust can't express.
+`TypedDict` classes need the `typing_extensions` package.
+Defaults to typescript."#)
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["typescript", "zod", "rust", "typed-dict", "pydantic", "go"]),
+        )
+        .arg(
+            clap::Arg::with_name("go-package")
+                .long("go-package")
+                .help(r#"The package of the Go target. Defaults to models."#)
+                .required(false)
+                .takes_value(true),
+        )
         .arg(
             clap::Arg::with_name("skip-type-name")
                 .long("skip-type-name")
@@ -88,19 +234,77 @@ Takes multiple occurences."#)
             return;
         }
     } else if matches.is_present("stdin") {
-        let mut buffer = String::new();
-        std::io::Read::read_to_string(&mut std::io::stdin(), &mut buffer).unwrap();
-        serde_yaml::from_str(&buffer).unwrap()
+        openapi::from_reader(std::io::stdin())
     } else {
         eprintln!("Please enter an input with '--input' or '--stdin'. See help for more info.");
         return;
     };
-    let skip = matches.is_present("skip-empty-types");
-    let typenames_to_skip = matches
-        .values_of("skip-type-name")
-        .unwrap_or_default()
-        .collect::<Vec<_>>();
-    let stringified = openapi::use_spec(&spec, skip, typenames_to_skip);
+    let options = openapi::Options {
+        skip_empty_types: matches.is_present("skip-empty-types"),
+        skip_type_names: matches
+            .values_of("skip-type-name")
+            .unwrap_or_default()
+            .map(String::from)
+            .collect(),
+        allow_additional_properties: matches.is_present("allow-additional-properties"),
+        sort_properties: matches.is_present("sort-properties"),
+        hoist_nested_types: matches.is_present("hoist-nested-types"),
+        skip_paths: matches.is_present("skip-paths"),
+        client: matches.is_present("client"),
+        branded_types: matches.is_present("branded-types"),
+        target: match matches.value_of("target") {
+            Some("zod") => openapi::Target::Zod,
+            Some("rust") => openapi::Target::Rust,
+            Some("typed-dict") => openapi::Target::TypedDict,
+            Some("pydantic") => openapi::Target::Pydantic,
+            Some("go") => openapi::Target::Go,
+            _ => openapi::Target::TypeScript,
+        },
+        go_package: matches.value_of("go-package").map(String::from),
+        format_mappings: {
+            let mut mappings = openapi::FormatMappings::default();
+            for mapping in matches.values_of("format-mapping").unwrap_or_default() {
+                let (ty, format, target) = openapi::FormatMappings::parse(mapping).unwrap();
+                mappings.insert(ty, format, target);
+            }
+            mappings
+        },
+        format: openapi::Format {
+            indent: match matches.value_of("indent") {
+                Some("tab") => "\t".to_string(),
+                Some(n) => " ".repeat(n.parse().unwrap()),
+                None => openapi::Format::default().indent,
+            },
+            quote: match matches.value_of("quote") {
+                Some("single") => openapi::Quote::Single,
+                _ => openapi::Quote::Double,
+            },
+            semicolons: !matches.is_present("no-semicolons"),
+            line_width: matches
+                .value_of("line-width")
+                .map(|n| n.parse().unwrap())
+                .unwrap_or_else(|| openapi::Format::default().line_width),
+            interfaces: matches.is_present("interfaces"),
+            enums: match matches.value_of("enums") {
+                Some("enum") => openapi::Enums::Enum,
+                Some("const") => openapi::Enums::Const,
+                _ => openapi::Enums::Union,
+            },
+        },
+    };
+    let output = spec.and_then(|spec| openapi::generate(&spec, &options));
+    let stringified = match output {
+        Ok(output) => {
+            for (key, name) in output.renames.iter() {
+                eprintln!("warning: renamed '{}' to '{}'", key, name);
+            }
+            output.code
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
     if let Some(write) = matches.value_of("write") {
         let mut file = std::fs::OpenOptions::new()
             .read(true)
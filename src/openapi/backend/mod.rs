@@ -0,0 +1,254 @@
+use crate::ir::{Definition, Operation};
+use crate::naming;
+use crate::print::{print_go, print_python, print_rust, Classes, Format, Printer};
+use crate::repr;
+
+/// A file generated by a [`Backend`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct File {
+    /// Relative to wherever the files are written, e.g. `types.ts`.
+    pub path: String,
+    pub contents: String,
+}
+
+/// What a [`Backend`] generates code from.
+#[derive(Clone, Copy, Debug)]
+pub struct Types<'a> {
+    /// Every named type, sorted by name, with the [`Options`](crate::Options) applied to them.
+    /// References between them are by name, and the names are valid and unique.
+    pub definitions: &'a [Definition],
+    /// The operations of `paths`, whose types are among the definitions.
+    /// Empty if the paths are skipped, unless the backend asks for them with [`Backend::operations`].
+    pub operations: &'a [Operation],
+}
+
+/// Generates the code of a language from the types of a specification.
+/// The built-in targets are implemented on top of it, and other languages can be added through
+/// [`generate_with`](crate::generate_with).
+pub trait Backend {
+    /// Names the definitions can't be given, e.g. the types the generated code refers to.
+    fn reserved(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Whether nested objects and string enums must be definitions of their own,
+    /// regardless of [`Options::hoist_nested_types`](crate::Options::hoist_nested_types).
+    fn hoist(&self) -> bool {
+        false
+    }
+
+    /// Whether the operations are needed regardless of [`Options::skip_paths`](crate::Options::skip_paths).
+    fn operations(&self) -> bool {
+        false
+    }
+
+    /// Suffixes of the names of the types the backend declares for every operation,
+    /// e.g. `Request` for `ListPetsRequest`, which no other type may be named with.
+    fn operation_suffixes(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Whether the backend declares the brands of [`Options::branded_types`](crate::Options::branded_types).
+    /// Otherwise the schemas are generated unbranded.
+    fn brands(&self) -> bool {
+        false
+    }
+
+    fn emit(&self, types: &Types) -> Vec<File>;
+}
+
+/// The first line of every generated file, commented out with `comment`.
+pub fn header(comment: &str) -> String {
+    format!(
+        "{} This file was generated using https://crates.io/crates/lupinas-lullaby\n",
+        comment
+    )
+}
+
+/// TypeScript types, and optionally a client calling the operations.
+#[derive(Clone, Debug, Default)]
+pub struct TypeScript {
+    pub format: Format,
+    /// A `createClient` function calling every operation with `fetch` is generated after the types.
+    pub client: bool,
+}
+
+impl Backend for TypeScript {
+    fn reserved(&self) -> &[&'static str] {
+        if self.client {
+            naming::CLIENT_RESERVED
+        } else {
+            &[]
+        }
+    }
+
+    fn operations(&self) -> bool {
+        self.client
+    }
+
+    fn operation_suffixes(&self) -> &[&'static str] {
+        if self.client {
+            &["Request", "Result"]
+        } else {
+            &[]
+        }
+    }
+
+    fn brands(&self) -> bool {
+        true
+    }
+
+    fn emit(&self, types: &Types) -> Vec<File> {
+        let definitions = lower(types.definitions);
+        let printer = Printer::with_definitions(&self.format, &definitions);
+        let code = printer
+            .print_brands(&definitions)
+            .into_iter()
+            .chain(
+                definitions
+                    .iter()
+                    .map(|definition| printer.print_definition(definition)),
+            )
+            .collect::<Vec<String>>()
+            .join("\n");
+        vec![File {
+            path: "types.ts".to_string(),
+            contents: typescript_file(&printer, code, types, self.client),
+        }]
+    }
+}
+
+/// [zod](https://zod.dev) schemas with their TypeScript types inferred from them,
+/// and optionally a client calling the operations.
+#[derive(Clone, Debug, Default)]
+pub struct Zod {
+    pub format: Format,
+    /// A `createClient` function calling every operation with `fetch` is generated after the schemas.
+    pub client: bool,
+}
+
+impl Backend for Zod {
+    fn reserved(&self) -> &[&'static str] {
+        if self.client {
+            naming::CLIENT_RESERVED
+        } else {
+            &[]
+        }
+    }
+
+    fn operations(&self) -> bool {
+        self.client
+    }
+
+    fn operation_suffixes(&self) -> &[&'static str] {
+        if self.client {
+            &["Request", "Result"]
+        } else {
+            &[]
+        }
+    }
+
+    fn brands(&self) -> bool {
+        true
+    }
+
+    fn emit(&self, types: &Types) -> Vec<File> {
+        let printer = Printer::with_definitions(&self.format, &lower(types.definitions));
+        let code = printer.print_zod(types.definitions);
+        vec![File {
+            path: "schemas.ts".to_string(),
+            contents: typescript_file(&printer, code, types, self.client),
+        }]
+    }
+}
+
+/// The definitions as the JavaScript types the TypeScript and zod printers work on.
+fn lower(definitions: &[Definition]) -> Vec<repr::Definition> {
+    definitions.iter().map(repr::Definition::from).collect()
+}
+
+/// The header, the declarations, and the client if there is one.
+fn typescript_file(printer: &Printer, code: String, types: &Types, client: bool) -> String {
+    let mut contents = header("//") + &code;
+    if client {
+        let names = types
+            .definitions
+            .iter()
+            .map(|definition| definition.name.clone())
+            .collect();
+        contents.push_str("\n\n");
+        contents.push_str(&printer.print_client(types.operations, &names));
+    }
+    contents
+}
+
+/// Rust structs and enums deriving serde's `Serialize` and `Deserialize`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rust;
+
+impl Backend for Rust {
+    fn reserved(&self) -> &[&'static str] {
+        naming::RUST_RESERVED
+    }
+
+    fn hoist(&self) -> bool {
+        true
+    }
+
+    fn emit(&self, types: &Types) -> Vec<File> {
+        vec![File {
+            path: "types.rs".to_string(),
+            contents: header("//") + &print_rust(types.definitions),
+        }]
+    }
+}
+
+/// A Python module of `TypedDict` classes or pydantic models, and type aliases.
+#[derive(Clone, Copy, Debug)]
+pub struct Python {
+    pub classes: Classes,
+}
+
+impl Backend for Python {
+    fn reserved(&self) -> &[&'static str] {
+        naming::PYTHON_RESERVED
+    }
+
+    fn hoist(&self) -> bool {
+        true
+    }
+
+    fn emit(&self, types: &Types) -> Vec<File> {
+        vec![File {
+            path: "types.py".to_string(),
+            contents: header("#") + &print_python(types.definitions, self.classes),
+        }]
+    }
+}
+
+/// A Go file of structs with `json` tags, string types with constants and type aliases.
+#[derive(Clone, Debug)]
+pub struct Go {
+    pub package: String,
+}
+
+impl Default for Go {
+    fn default() -> Self {
+        Go {
+            package: "models".to_string(),
+        }
+    }
+}
+
+impl Backend for Go {
+    fn hoist(&self) -> bool {
+        true
+    }
+
+    fn emit(&self, types: &Types) -> Vec<File> {
+        vec![File {
+            path: format!("{}.go", self.package),
+            contents: header("//") + &print_go(types.definitions, &self.package),
+        }]
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+/// Everything that can go wrong while reading a specification and turning it into types.
+#[derive(Debug)]
+pub enum Error {
+    /// The specification could not be read.
+    Io(std::io::Error),
+    /// The specification is not valid YAML/JSON, or does not have the shape of a specification.
+    Syntax(serde_yaml::Error),
+    /// The document is neither a Swagger 2.0 nor an OpenAPI 3.0/3.1 document.
+    /// Contains the version that was found, if any.
+    UnknownVersion(Option<String>),
+    /// The `$ref` found at `pointer` could not be resolved.
+    UnresolvedReference { pointer: String, reference: String },
+    /// The `$ref` found at `pointer` eventually refers back to itself without ever reaching a definition.
+    CyclicReference { pointer: String, reference: String },
+    /// The schema found at `pointer` uses a construct that cannot be converted.
+    UnsupportedSchema { pointer: String, reason: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "unable to read specification: {}", e),
+            Error::Syntax(e) => write!(f, "unable to parse specification: {}", e),
+            Error::UnknownVersion(Some(version)) => {
+                write!(f, "unsupported specification version '{}'", version)
+            }
+            Error::UnknownVersion(None) => write!(
+                f,
+                "unable to find the specification version, expected a 'swagger' or 'openapi' field"
+            ),
+            Error::UnresolvedReference { pointer, reference } => {
+                write!(
+                    f,
+                    "unable to resolve reference '{}' at '{}'",
+                    reference, pointer
+                )
+            }
+            Error::CyclicReference { pointer, reference } => {
+                write!(f, "reference '{}' at '{}' is cyclic", reference, pointer)
+            }
+            Error::UnsupportedSchema { pointer, reason } => {
+                write!(f, "unsupported schema at '{}': {}", pointer, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Syntax(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        Error::Syntax(e)
+    }
+}
@@ -0,0 +1,892 @@
+use indexmap::IndexMap;
+
+use crate::naming;
+use crate::repr::{Documentation, JavaScriptValue};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// What the specification says about a schema besides the values it accepts.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    /// The JSON pointer of the schema in the specification, e.g. `#/components/schemas/Pet/properties/name`.
+    pub pointer: String,
+    pub docs: Documentation,
+    /// `null` is accepted too, from `nullable` in OpenAPI 3.0 or `x-nullable` in Swagger 2.0.
+    pub nullable: bool,
+    /// `readOnly`, the value is only sent in responses.
+    pub read_only: bool,
+    /// `writeOnly`, the value is only sent in requests.
+    pub write_only: bool,
+}
+
+/// A schema, independent of the language it is generated in.
+#[derive(Debug, Clone)]
+pub struct Type {
+    pub kind: Kind,
+    pub meta: Metadata,
+}
+
+/// A property of an object.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub required: bool,
+    pub schema: Type,
+}
+
+/// What an object accepts besides its named properties.
+#[derive(Debug, Clone)]
+pub enum Additional {
+    /// The schema does not say, which means anything is accepted.
+    Unspecified,
+    /// `additionalProperties: false`.
+    Forbidden,
+    /// `additionalProperties: true` or a schema.
+    Allowed(Box<Type>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Kind {
+    /// Any value, e.g. from a schema without assertions or `true`.
+    Any,
+    /// No value, from `false`.
+    Never,
+    /// `type: unknown`, which some specifications use for any value.
+    Unknown,
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    /// A string holding a date, or a date and a time.
+    Date,
+    /// The type a `type` and a `format` are mapped to, see [`FormatMappings`].
+    Mapped(String),
+    /// A nominal type named after its brand, from `x-brand` or a format of [`BRANDED_FORMATS`],
+    /// accepting the values of the inner type without being interchangeable with it.
+    Branded(String, Box<Type>),
+    /// Exactly the value, from `const`.
+    Const(JavaScriptValue),
+    /// One of the values, from `enum`.
+    Enum(Vec<JavaScriptValue>),
+    Array(Box<Type>),
+    /// A fixed list of elements, optionally followed by any number of elements of another type.
+    Tuple(Vec<Type>, Option<Box<Type>>),
+    Object(IndexMap<String, Field>, Additional),
+    /// A schema of the specification, by its type name.
+    Reference(String),
+    /// A value of any of the types, from `oneOf`, `anyOf` or a list of `type`s.
+    Union(Vec<Type>),
+    /// A value of all of the types, from `allOf` or from assertions besides a `$ref` or a composition.
+    Intersection(Vec<Type>),
+    /// Any value except the ones of the type, from `not`.
+    Not(Box<Type>),
+    /// A variant of a discriminated union, whose `property` is one of `values`.
+    Tagged {
+        variant: Box<Type>,
+        property: String,
+        values: Vec<JavaScriptValue>,
+    },
+    /// A `type` the specification doesn't define, kept as is.
+    Other(String),
+}
+
+/// The brands of the string formats, which strings of those formats are branded with
+/// when branded types are enabled, unless they have an `x-brand` of their own.
+pub const BRANDED_FORMATS: &[(&str, &str)] = &[
+    ("uuid", "Uuid"),
+    ("email", "Email"),
+    ("uri", "Uri"),
+    ("date", "DateString"),
+];
+
+/// A named schema of the specification, or a type of an operation.
+#[derive(Debug, Clone)]
+pub struct Definition {
+    /// The type name it was given, which is valid and unique.
+    pub name: String,
+    pub schema: Type,
+}
+
+/// Where the value of a parameter is sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+impl ParameterLocation {
+    /// The location named by the `in` of a parameter.
+    pub fn parse(location: &str) -> Option<ParameterLocation> {
+        match location {
+            "path" => Some(ParameterLocation::Path),
+            "query" => Some(ParameterLocation::Query),
+            "header" => Some(ParameterLocation::Header),
+            "cookie" => Some(ParameterLocation::Cookie),
+            _ => None,
+        }
+    }
+
+    /// The style parameters are serialized with when their `style` isn't given.
+    pub fn default_style(self) -> &'static str {
+        match self {
+            ParameterLocation::Path | ParameterLocation::Header => "simple",
+            ParameterLocation::Query | ParameterLocation::Cookie => "form",
+        }
+    }
+}
+
+/// A parameter of an operation, whose schema is documented with the parameter's description.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub location: ParameterLocation,
+    /// How arrays and objects are serialized, e.g. `form` or `pipeDelimited`.
+    pub style: String,
+    /// Whether the elements of arrays and objects are serialized as separate parameters.
+    pub explode: bool,
+    pub field: Field,
+}
+
+/// The body of a request, sent as `media_type`, whose schema is documented with the body's description.
+#[derive(Debug, Clone)]
+pub struct RequestBody {
+    pub media_type: String,
+    pub field: Field,
+}
+
+/// A response of an operation, `schema` being the type of its body if it has one.
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// The status code, a range like `2XX` or `default`.
+    pub status: String,
+    pub schema: Option<Type>,
+    pub docs: Documentation,
+}
+
+/// An operation of `paths` or `webhooks`.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    /// The prefix of the names of its types, from its `operationId` or its method and path,
+    /// or from the name of its webhook.
+    pub name: String,
+    /// In lowercase, e.g. `get`.
+    pub method: String,
+    pub path: String,
+    pub docs: Documentation,
+    /// The parameters of the operation and of its path, the former overriding the latter.
+    pub parameters: Vec<Parameter>,
+    pub request_body: Option<RequestBody>,
+    pub responses: Vec<Response>,
+    /// The operation is one of `webhooks`, whose requests are sent by the API rather than to it,
+    /// so `path` is the name of the webhook and the client doesn't call it.
+    pub webhook: bool,
+}
+
+impl Type {
+    pub fn new(kind: Kind, meta: Metadata) -> Type {
+        Type { kind, meta }
+    }
+
+    /// A type of no schema in particular, e.g. the elements of an array without `items`.
+    pub fn at(kind: Kind, pointer: &str) -> Type {
+        Type {
+            kind,
+            meta: Metadata {
+                pointer: pointer.to_string(),
+                ..Metadata::default()
+            },
+        }
+    }
+
+    /// Narrows the `property` of the type to one of `values`, making it a variant of a discriminated union.
+    pub fn tagged(self, property: &str, values: Vec<JavaScriptValue>) -> Type {
+        let meta = Metadata {
+            nullable: false,
+            ..self.meta.clone()
+        };
+        Type {
+            kind: Kind::Tagged {
+                variant: Box::new(self),
+                property: property.to_string(),
+                values,
+            },
+            meta,
+        }
+    }
+
+    /// The type with its unions simplified, and the ones nested in it:
+    /// their `null` variants make them nullable instead, and a union of a single type is that type.
+    /// A type accepting `null` among other values is then always [`Metadata::nullable`].
+    pub fn simplified(&self) -> Type {
+        let ty = self.map(Type::simplified);
+        let is_null = |t: &Type| matches!(t.kind, Kind::Null | Kind::Const(JavaScriptValue::Null));
+        match ty.kind {
+            Kind::Union(variants) => {
+                let (nulls, mut rest): (Vec<_>, Vec<_>) = variants.into_iter().partition(is_null);
+                let nullable = ty.meta.nullable || !nulls.is_empty();
+                match rest.len() {
+                    0 => Type::new(
+                        Kind::Null,
+                        Metadata {
+                            nullable: false,
+                            ..ty.meta
+                        },
+                    ),
+                    1 => {
+                        let variant = rest.remove(0);
+                        Type::new(
+                            variant.kind,
+                            Metadata {
+                                nullable: nullable || variant.meta.nullable,
+                                ..ty.meta
+                            },
+                        )
+                    }
+                    _ => Type::new(
+                        Kind::Union(rest),
+                        Metadata {
+                            nullable,
+                            ..ty.meta
+                        },
+                    ),
+                }
+            }
+            kind @ (Kind::Null | Kind::Const(JavaScriptValue::Null)) => Type::new(
+                kind,
+                Metadata {
+                    nullable: false,
+                    ..ty.meta
+                },
+            ),
+            kind => Type::new(kind, ty.meta),
+        }
+    }
+
+    /// The type with `f` applied to the types nested in it, e.g. the items of an array.
+    fn map(&self, mut f: impl FnMut(&Type) -> Type) -> Type {
+        let kind = match &self.kind {
+            Kind::Branded(brand, t) => Kind::Branded(brand.clone(), Box::new(f(t))),
+            Kind::Array(t) => Kind::Array(Box::new(f(t))),
+            Kind::Tuple(elements, rest) => Kind::Tuple(
+                elements.iter().map(&mut f).collect(),
+                rest.as_ref().map(|t| Box::new(f(t))),
+            ),
+            Kind::Object(fields, additional) => Kind::Object(
+                fields
+                    .iter()
+                    .map(|(k, field)| {
+                        let schema = f(&field.schema);
+                        (k.clone(), Field { schema, ..*field })
+                    })
+                    .collect(),
+                match additional {
+                    Additional::Allowed(t) => Additional::Allowed(Box::new(f(t))),
+                    additional => additional.clone(),
+                },
+            ),
+            Kind::Union(variants) => Kind::Union(variants.iter().map(&mut f).collect()),
+            Kind::Intersection(parts) => Kind::Intersection(parts.iter().map(&mut f).collect()),
+            Kind::Not(t) => Kind::Not(Box::new(f(t))),
+            Kind::Tagged {
+                variant,
+                property,
+                values,
+            } => Kind::Tagged {
+                variant: Box::new(f(variant)),
+                property: property.clone(),
+                values: values.clone(),
+            },
+            kind => kind.clone(),
+        };
+        Type::new(kind, self.meta.clone())
+    }
+
+    /// The type without `null`.
+    pub fn non_null(&self) -> Type {
+        Type::new(
+            self.kind.clone(),
+            Metadata {
+                nullable: false,
+                ..self.meta.clone()
+            },
+        )
+    }
+
+    /// The parts of an intersection, including the ones of the intersections nested in it
+    /// and the object narrowing the discriminator of a variant, or the type itself if it isn't one.
+    pub fn intersected(&self) -> Vec<Cow<'_, Type>> {
+        match &self.kind {
+            _ if self.meta.nullable => vec![Cow::Borrowed(self)],
+            Kind::Intersection(parts) => parts.iter().flat_map(Type::intersected).collect(),
+            Kind::Tagged {
+                variant,
+                property,
+                values,
+            } => {
+                let mut parts = variant.intersected();
+                parts.push(Cow::Owned(Type::tag(property, values, &self.meta.pointer)));
+                parts
+            }
+            _ => vec![Cow::Borrowed(self)],
+        }
+    }
+
+    /// The object a variant of a discriminated union is intersected with, whose `property` is one of `values`.
+    pub fn tag(property: &str, values: &[JavaScriptValue], pointer: &str) -> Type {
+        let kind = match values {
+            [value] => Kind::Const(value.clone()),
+            values => Kind::Enum(values.to_vec()),
+        };
+        let mut fields = IndexMap::new();
+        fields.insert(
+            property.to_string(),
+            Field {
+                required: true,
+                schema: Type::at(kind, pointer),
+            },
+        );
+        Type::at(Kind::Object(fields, Additional::Unspecified), pointer)
+    }
+
+    /// The values of a string enum, i.e. an `enum` or a union of `const`s of strings, which isn't nullable.
+    pub fn string_enum_values(&self) -> Option<Vec<&str>> {
+        fn string(value: &JavaScriptValue) -> Option<&str> {
+            match value {
+                JavaScriptValue::String(s) => Some(s.as_str()),
+                _ => None,
+            }
+        }
+
+        match &self.kind {
+            _ if self.meta.nullable => None,
+            Kind::Enum(values) => values.iter().map(string).collect(),
+            Kind::Union(variants) => variants
+                .iter()
+                .map(|v| match &v.kind {
+                    Kind::Const(value) if !v.meta.nullable => string(value),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// The names of the schemas referred to, in order of appearance and with duplicates.
+    pub fn references(&self) -> Vec<&str> {
+        match &self.kind {
+            Kind::Reference(name) => vec![name.as_str()],
+            Kind::Branded(_, t) | Kind::Array(t) | Kind::Not(t) => t.references(),
+            Kind::Tuple(elements, rest) => elements
+                .iter()
+                .chain(rest.iter().map(Box::as_ref))
+                .flat_map(Type::references)
+                .collect(),
+            Kind::Object(fields, additional) => fields
+                .values()
+                .map(|field| &field.schema)
+                .chain(match additional {
+                    Additional::Allowed(t) => Some(t.as_ref()),
+                    _ => None,
+                })
+                .flat_map(Type::references)
+                .collect(),
+            Kind::Union(types) | Kind::Intersection(types) => {
+                types.iter().flat_map(Type::references).collect()
+            }
+            Kind::Tagged { variant, .. } => variant.references(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Kind {
+    /// The kind branded as `brand`, made a valid type name.
+    pub fn branded(self, brand: &str, pointer: &str) -> Kind {
+        let brand = if naming::is_identifier(brand) {
+            brand.to_string()
+        } else {
+            naming::identifier(brand)
+        };
+        Kind::Branded(brand, Box::new(Type::at(self, pointer)))
+    }
+
+    /// The kind of a string of `format`, branded after the format if it is one of [`BRANDED_FORMATS`].
+    /// The format mappings don't apply to branded strings.
+    pub fn branded_string(self, format: Option<&str>, pointer: &str) -> Kind {
+        match BRANDED_FORMATS.iter().find(|(f, _)| Some(*f) == format) {
+            Some((_, brand)) => Kind::String.branded(brand, pointer),
+            None => self,
+        }
+    }
+}
+
+impl Operation {
+    /// The names of the parameter types, by location.
+    /// Cookies are left out because they are sent by the browser rather than passed explicitly.
+    pub const PARAMETERS: &'static [(ParameterLocation, &'static str)] = &[
+        (ParameterLocation::Path, "PathParams"),
+        (ParameterLocation::Query, "QueryParams"),
+        (ParameterLocation::Header, "Headers"),
+    ];
+
+    /// The name of one of the types of the operation, e.g. `ListPetsQueryParams`.
+    pub fn type_name(&self, suffix: &str) -> String {
+        format!("{}{}", self.name, suffix)
+    }
+
+    /// The name of the type of the body of the response with the given status, e.g. `ListPetsResponse200`.
+    pub fn response_type_name(&self, status: &str) -> String {
+        if status == "default" {
+            self.type_name("ResponseDefault")
+        } else {
+            self.type_name(&format!("Response{}", status.to_ascii_uppercase()))
+        }
+    }
+
+    /// The types of the parameters, body and responses of the operation.
+    /// Kinds of parameters the operation has none of don't get a type.
+    pub fn definitions(&self) -> Vec<Definition> {
+        let parameters = Operation::PARAMETERS
+            .iter()
+            .filter_map(|(location, suffix)| {
+                let fields = self
+                    .parameters
+                    .iter()
+                    .filter(|p| p.location == *location)
+                    .map(|p| (p.name.clone(), p.field.clone()))
+                    .collect::<IndexMap<_, _>>();
+                (!fields.is_empty()).then(|| Definition {
+                    name: self.type_name(suffix),
+                    schema: Type::new(
+                        Kind::Object(fields, Additional::Forbidden),
+                        Metadata::default(),
+                    ),
+                })
+            });
+        let request_body = self.request_body.iter().map(|body| Definition {
+            name: self.type_name("RequestBody"),
+            schema: body.field.schema.clone(),
+        });
+        let responses = self.responses.iter().filter_map(|response| {
+            response.schema.as_ref().map(|schema| Definition {
+                name: self.response_type_name(&response.status),
+                schema: Type::new(
+                    schema.kind.clone(),
+                    Metadata {
+                        docs: response.docs.clone(),
+                        ..schema.meta.clone()
+                    },
+                ),
+            })
+        });
+        parameters.chain(request_body).chain(responses).collect()
+    }
+}
+
+/// Makes the names of the operations unique, so that none of their types, including the ones with
+/// the given `suffixes`, collides with another one or with a definition, by suffixing them with 2, 3...
+pub fn name_operations(
+    operations: &mut [Operation],
+    definitions: &[Definition],
+    suffixes: &[&str],
+) {
+    let mut taken = definitions
+        .iter()
+        .map(|d| d.name.clone())
+        .collect::<HashSet<_>>();
+    for operation in operations.iter_mut() {
+        let base = operation.name.clone();
+        for idx in 1.. {
+            operation.name = if idx == 1 {
+                base.clone()
+            } else {
+                format!("{}{}", base, idx)
+            };
+            let names = operation
+                .definitions()
+                .into_iter()
+                .map(|d| d.name)
+                .chain(suffixes.iter().map(|suffix| operation.type_name(suffix)))
+                .collect::<Vec<_>>();
+            if !taken.contains(&operation.name) && names.iter().all(|n| !taken.contains(n)) {
+                taken.insert(operation.name.clone());
+                taken.extend(names);
+                break;
+            }
+        }
+    }
+}
+
+/// The types the schemas of a `type` and a `format` are generated as,
+/// e.g. `Date` for `string` and `date-time`, or `bigint` for `integer` and `int64`.
+/// Besides `string`, `number`, `boolean` and `Date`, which every target knows of,
+/// a type is a TypeScript type, which the other targets don't know of.
+#[derive(Clone, Debug)]
+pub struct FormatMappings(IndexMap<(String, String), String>);
+
+impl Default for FormatMappings {
+    /// Dates are `Date`, as they are in the TypeScript types.
+    fn default() -> Self {
+        let mut mappings = FormatMappings(IndexMap::new());
+        mappings.insert("string", "date", "Date");
+        mappings.insert("string", "date-time", "Date");
+        mappings
+    }
+}
+
+impl FormatMappings {
+    /// No format is mapped, so formatted schemas are the type they would be without a format.
+    pub fn empty() -> FormatMappings {
+        FormatMappings(IndexMap::new())
+    }
+
+    /// Maps `ty` and `format` to `target`, overriding what they were mapped to.
+    pub fn insert(&mut self, ty: &str, format: &str, target: &str) {
+        self.0
+            .insert((ty.to_string(), format.to_string()), target.to_string());
+    }
+
+    /// The type, the format and the target of a mapping written as `type:format=target`.
+    pub fn parse(mapping: &str) -> Option<(&str, &str, &str)> {
+        let (source, target) = mapping.split_once('=')?;
+        let (ty, format) = source.split_once(':')?;
+        Some((ty.trim(), format.trim(), target.trim())).filter(|(ty, format, target)| {
+            !ty.is_empty() && !format.is_empty() && !target.is_empty()
+        })
+    }
+
+    pub fn get(&self, ty: &str, format: &str) -> Option<&str> {
+        self.0
+            .get(&(ty.to_string(), format.to_string()))
+            .map(String::as_str)
+    }
+
+    /// What a schema of `ty` and `format` is, `kind` being what it is without a format.
+    pub fn kind(&self, ty: &str, format: Option<&str>, kind: Kind) -> Kind {
+        match format.and_then(|format| self.get(ty, format)) {
+            Some("string") => Kind::String,
+            Some("number") if ty == "integer" => Kind::Integer,
+            Some("number") => Kind::Number,
+            Some("boolean") => Kind::Boolean,
+            Some("Date") => Kind::Date,
+            Some(target) => Kind::Mapped(target.to_string()),
+            None => kind,
+        }
+    }
+}
+
+/// Makes the openness of objects explicit: unless `additionalProperties: false` is given,
+/// an object accepts unknown properties in addition to its named ones.
+pub fn allow_additional_properties(ty: &Type) -> Type {
+    let ty = ty.map(allow_additional_properties);
+    match ty.kind {
+        Kind::Object(fields, Additional::Unspecified) => {
+            let unknown = Type::at(Kind::Unknown, &ty.meta.pointer);
+            Type::new(
+                Kind::Object(fields, Additional::Allowed(Box::new(unknown))),
+                ty.meta,
+            )
+        }
+        kind => Type::new(kind, ty.meta),
+    }
+}
+
+/// Moves the objects and string enums nested in properties into definitions of their own,
+/// named after their `title` or after the definition and the property, e.g. `OrderStatus`.
+/// Identical nested types share a single definition.
+pub fn hoist_nested_types(definitions: Vec<Definition>) -> Vec<Definition> {
+    let mut hoister = Hoister {
+        taken: definitions.iter().map(|d| d.name.clone()).collect(),
+        hoisted: vec![],
+        names: HashMap::new(),
+    };
+    let mut definitions = definitions
+        .into_iter()
+        .map(|definition| Definition {
+            schema: hoister.hoist(&definition.schema, &definition.name),
+            ..definition
+        })
+        .collect::<Vec<_>>();
+    definitions.extend(hoister.hoisted);
+    definitions
+}
+
+struct Hoister {
+    /// The names of every definition, including the hoisted ones.
+    taken: HashSet<String>,
+    hoisted: Vec<Definition>,
+    /// The name of each hoisted type, by its [`signature`].
+    names: HashMap<String, String>,
+}
+
+impl Hoister {
+    fn hoist(&mut self, ty: &Type, parent: &str) -> Type {
+        match &ty.kind {
+            Kind::Object(fields, additional) => Type::new(
+                Kind::Object(
+                    fields
+                        .iter()
+                        .map(|(k, field)| {
+                            let docs = &field.schema.meta.docs;
+                            let schema = self.hoist_property(&field.schema, parent, k, docs);
+                            (k.clone(), Field { schema, ..*field })
+                        })
+                        .collect(),
+                    match additional {
+                        Additional::Allowed(t) => {
+                            Additional::Allowed(Box::new(self.hoist(t, parent)))
+                        }
+                        additional => additional.clone(),
+                    },
+                ),
+                ty.meta.clone(),
+            ),
+            _ => ty.map(|t| self.hoist(t, parent)),
+        }
+    }
+
+    /// Hoists the type of a property if it is an object or a string enum, possibly in an array,
+    /// `docs` being the documentation of the property.
+    fn hoist_property(
+        &mut self,
+        ty: &Type,
+        parent: &str,
+        property: &str,
+        docs: &Documentation,
+    ) -> Type {
+        match &ty.kind {
+            Kind::Array(items) => Type::new(
+                Kind::Array(Box::new(self.hoist_property(items, parent, property, docs))),
+                ty.meta.clone(),
+            ),
+            Kind::Object(fields, _) if !fields.is_empty() => {
+                self.hoist_definition(ty, parent, property, docs)
+            }
+            _ if ty.non_null().string_enum_values().is_some() => {
+                self.hoist_definition(ty, parent, property, docs)
+            }
+            _ => self.hoist(ty, parent),
+        }
+    }
+
+    fn hoist_definition(
+        &mut self,
+        ty: &Type,
+        parent: &str,
+        property: &str,
+        docs: &Documentation,
+    ) -> Type {
+        let base = match docs.title.as_ref() {
+            Some(title) => naming::identifier(title),
+            None => format!("{}{}", parent, naming::pascal_case(property)),
+        };
+        let name = naming::unique(&base, &self.taken);
+        // The name is taken before hoisting the nested types so that they are named after it.
+        self.taken.insert(name.clone());
+        let schema = Type::new(
+            self.hoist(&ty.non_null(), &name).kind,
+            Metadata {
+                pointer: ty.meta.pointer.clone(),
+                docs: Documentation {
+                    enum_names: docs.enum_names.clone(),
+                    ..Documentation::default()
+                },
+                ..Metadata::default()
+            },
+        );
+        let signature = signature(&schema);
+        let name = match self.names.get(&signature) {
+            Some(existing) => {
+                self.taken.remove(&name);
+                existing.clone()
+            }
+            None => {
+                self.names.insert(signature, name.clone());
+                self.hoisted.push(Definition {
+                    name: name.clone(),
+                    schema,
+                });
+                name
+            }
+        };
+        Type::new(Kind::Reference(name), ty.meta.clone())
+    }
+}
+
+/// What tells a type apart from the others, regardless of where it is in the specification.
+fn signature(ty: &Type) -> String {
+    fn unlocated(ty: &Type) -> Type {
+        let mut ty = ty.map(unlocated);
+        ty.meta.pointer.clear();
+        ty
+    }
+    format!("{:?}", unlocated(ty).kind)
+}
+
+/// Sorts the properties of every object by name instead of keeping the order of the specification.
+pub fn sort_properties(ty: &Type) -> Type {
+    let mut ty = ty.map(sort_properties);
+    if let Kind::Object(fields, _) = &mut ty.kind {
+        fields.sort_keys();
+    }
+    ty
+}
+
+/// The type without the objects that have no properties and don't accept any, `None` if nothing is left of it.
+pub fn filter_empty_types(ty: &Type) -> Option<Type> {
+    filter_unwanted_types(ty, &[])
+}
+
+/// The type without the references to `skip_types`, nor the objects that are left without properties,
+/// `None` if nothing is left of it.
+pub fn filter_unwanted_types(ty: &Type, skip_types: &[&str]) -> Option<Type> {
+    let filter = |t: &Type| filter_unwanted_types(t, skip_types);
+    let kind = match &ty.kind {
+        Kind::Reference(name) | Kind::Mapped(name) | Kind::Other(name)
+            if skip_types.contains(&name.as_str()) =>
+        {
+            return None
+        }
+        Kind::Branded(brand, t) => Kind::Branded(brand.clone(), Box::new(filter(t)?)),
+        Kind::Array(t) => Kind::Array(Box::new(filter(t)?)),
+        Kind::Tuple(elements, rest) => Kind::Tuple(
+            elements.iter().map(filter).collect::<Option<Vec<_>>>()?,
+            match rest {
+                Some(t) => Some(Box::new(filter(t)?)),
+                None => None,
+            },
+        ),
+        Kind::Enum(values) if values.is_empty() => return None,
+        Kind::Object(fields, additional) => {
+            let fields = fields
+                .iter()
+                .filter_map(|(k, field)| {
+                    filter(&field.schema).map(|schema| (k.clone(), Field { schema, ..*field }))
+                })
+                .collect::<IndexMap<_, _>>();
+            let additional = match additional {
+                Additional::Allowed(t) => filter(t)
+                    .map(|t| Additional::Allowed(Box::new(t)))
+                    .unwrap_or(Additional::Unspecified),
+                additional => additional.clone(),
+            };
+            if fields.is_empty() && !matches!(additional, Additional::Allowed(_)) {
+                return None;
+            }
+            Kind::Object(fields, additional)
+        }
+        Kind::Union(variants) => {
+            let variants = variants.iter().filter_map(filter).collect::<Vec<_>>();
+            if variants.is_empty() {
+                return None;
+            }
+            Kind::Union(variants)
+        }
+        Kind::Intersection(parts) => {
+            let parts = parts.iter().filter_map(filter).collect::<Vec<_>>();
+            if parts.is_empty() {
+                return None;
+            }
+            Kind::Intersection(parts)
+        }
+        // The object narrowing the discriminator is left if the variant isn't.
+        Kind::Tagged {
+            variant,
+            property,
+            values,
+        } => match filter(variant) {
+            Some(variant) => Kind::Tagged {
+                variant: Box::new(variant),
+                property: property.clone(),
+                values: values.clone(),
+            },
+            None => return Some(Type::tag(property, values, &ty.meta.pointer)),
+        },
+        kind => kind.clone(),
+    };
+    Some(Type::new(kind, ty.meta.clone()))
+}
+
+/// The definitions grouped by the cycles of references between them, each group coming after the ones it refers to.
+/// A definition which isn't part of a cycle is in a group of its own.
+pub fn dependency_order(definitions: &[Definition]) -> Vec<Vec<&Definition>> {
+    struct Tarjan<'a> {
+        definitions: &'a [Definition],
+        indices: HashMap<&'a str, usize>,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        groups: Vec<Vec<&'a Definition>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+            let definitions = self.definitions;
+            for name in definitions[v].schema.references() {
+                let w = match self.indices.get(name) {
+                    Some(w) => *w,
+                    None => continue,
+                };
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(index) if self.on_stack[w] => self.low[v] = self.low[v].min(index),
+                    Some(_) => (),
+                }
+            }
+            if Some(self.low[v]) == self.index[v] {
+                let mut group = vec![];
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    group.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                group.sort_unstable();
+                self.groups
+                    .push(group.into_iter().map(|w| &definitions[w]).collect());
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        definitions,
+        indices: definitions
+            .iter()
+            .enumerate()
+            .map(|(idx, d)| (d.name.as_str(), idx))
+            .collect(),
+        index: vec![None; definitions.len()],
+        low: vec![0; definitions.len()],
+        stack: vec![],
+        on_stack: vec![false; definitions.len()],
+        next: 0,
+        groups: vec![],
+    };
+    for v in 0..definitions.len() {
+        if tarjan.index[v].is_none() {
+            tarjan.visit(v);
+        }
+    }
+    tarjan.groups
+}
+
+/// Whether the definitions of a group of [`dependency_order`] refer to themselves.
+pub fn is_recursive(group: &[&Definition]) -> bool {
+    group.len() > 1
+        || group
+            .iter()
+            .any(|d| d.schema.references().contains(&d.name.as_str()))
+}
@@ -1,62 +1,346 @@
+pub mod backend;
+mod error;
+pub mod ir;
+mod naming;
+mod pointer;
+mod print;
 mod repr;
+mod resolve;
 mod spec2;
 mod spec3;
 
-use self::repr::filter_empty_types;
+use self::backend::{Backend, Types};
+pub use self::error::Error;
+use self::ir::{
+    allow_additional_properties, filter_empty_types, filter_unwanted_types, hoist_nested_types,
+    name_operations, sort_properties,
+};
+pub use self::ir::{
+    dependency_order, is_recursive, FormatMappings, Operation, Parameter, ParameterLocation,
+    RequestBody, Response, BRANDED_FORMATS,
+};
+pub use self::print::{Classes, Enums, Format, Quote};
+pub use self::repr::{
+    AdditionalProperties, Definition, Documentation, JavaScriptType, JavaScriptValue, Property,
+};
 use self::{
-    spec2::{use_spec2, Spec2},
-    spec3::{use_spec3, Spec3},
+    spec2::{use_spec2, use_spec2_paths, Spec2},
+    spec3::{use_spec3, use_spec3_paths, Spec3},
 };
-use repr::filter_unwanted_types;
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_yaml::Value;
 use std::{fs::File, io::Read, path::Path};
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, PartialEq)]
 #[serde(untagged)]
 pub enum OpenApi {
     V2(Spec2),
     V3(Spec3),
+    /// OpenAPI 3.1, whose schemas follow JSON Schema 2020-12.
+    V31(Spec3),
+}
+
+impl OpenApi {
+    /// Interprets an already parsed YAML/JSON document as a specification,
+    /// picking the version based on its `swagger` or `openapi` field.
+    /// References to other files cannot be resolved because the location of the document is unknown,
+    /// use [`from_path`] for documents split across multiple files.
+    pub fn from_value(value: Value) -> Result<OpenApi, Error> {
+        OpenApi::from_document(value, None)
+    }
+
+    fn from_document(value: Value, path: Option<&Path>) -> Result<OpenApi, Error> {
+        let version = |key: &str| match value.get(key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        match (version("swagger"), version("openapi")) {
+            (Some(version), _) if version == "2.0" || version == "2" => Ok(OpenApi::V2(
+                serde_yaml::from_value(resolve::resolve(value, path)?)?,
+            )),
+            (None, Some(version)) if version.starts_with("3.0") => Ok(OpenApi::V3(
+                serde_yaml::from_value(resolve::resolve(value, path)?)?,
+            )),
+            (None, Some(version)) if version.starts_with("3.1") => Ok(OpenApi::V31(
+                serde_yaml::from_value(resolve::resolve(value, path)?)?,
+            )),
+            (Some(version), _) | (None, Some(version)) => Err(Error::UnknownVersion(Some(version))),
+            (None, None) => Err(Error::UnknownVersion(None)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OpenApi {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        OpenApi::from_value(value).map_err(serde::de::Error::custom)
+    }
 }
 
-pub fn from_path<P>(path: P) -> OpenApi
+pub fn from_path<P>(path: P) -> Result<OpenApi, Error>
 where
     P: AsRef<Path>,
 {
-    from_reader(File::open(path).unwrap())
+    let value = serde_yaml::from_reader::<_, Value>(File::open(path.as_ref())?)?;
+    OpenApi::from_document(value, Some(path.as_ref()))
 }
 
-pub fn from_reader<R>(read: R) -> OpenApi
+pub fn from_reader<R>(read: R) -> Result<OpenApi, Error>
 where
     R: Read,
 {
-    serde_yaml::from_reader::<R, OpenApi>(read).unwrap()
+    OpenApi::from_value(serde_yaml::from_reader::<R, Value>(read)?)
 }
 
-pub fn from_bytes(read: &[u8]) -> OpenApi {
-    serde_yaml::from_slice::<OpenApi>(read).unwrap()
+pub fn from_bytes(read: &[u8]) -> Result<OpenApi, Error> {
+    OpenApi::from_value(serde_yaml::from_slice::<Value>(read)?)
 }
 
-pub fn use_spec(spec: &OpenApi, skip_empty: bool, skip_types: Vec<&str>) -> String {
-    let types = match spec {
-        OpenApi::V2(spec) => use_spec2(spec),
-        OpenApi::V3(spec) => use_spec3(spec),
+/// What the definitions are generated as.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Target {
+    /// TypeScript types.
+    #[default]
+    TypeScript,
+    /// [zod](https://zod.dev) schemas, with their TypeScript types inferred from them.
+    Zod,
+    /// Rust structs and enums deriving serde's `Serialize` and `Deserialize`.
+    /// Nested objects and string enums are always hoisted, and the format doesn't apply.
+    Rust,
+    /// A Python module of `TypedDict` classes and type aliases.
+    /// Nested objects and string enums are always hoisted, and the format doesn't apply.
+    TypedDict,
+    /// A Python module of pydantic models and type aliases.
+    /// Nested objects and string enums are always hoisted, and the format doesn't apply.
+    Pydantic,
+    /// A Go file of structs with `json` tags, string types with constants and type aliases.
+    /// Nested objects and string enums are always hoisted, and the format doesn't apply.
+    Go,
+}
+
+/// Controls how the types are generated.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Skip empty types because some linter will complain.
+    pub skip_empty_types: bool,
+    /// Skip types with the given names.
+    pub skip_type_names: Vec<String>,
+    /// Objects accept unknown properties unless their schema says `additionalProperties: false`,
+    /// as JSON Schema has it, so they get an index signature of `unknown`.
+    /// This makes the types more permissive, not stricter.
+    pub allow_additional_properties: bool,
+    /// Properties are sorted by name instead of following the specification.
+    /// Types are always sorted by name.
+    pub sort_properties: bool,
+    /// Nested objects and string enums are declared as types of their own.
+    pub hoist_nested_types: bool,
+    /// Only the definitions are generated, not the parameters, bodies and responses of the operations of `paths`.
+    pub skip_paths: bool,
+    /// A `createClient` function calling every operation with `fetch` is generated after the types.
+    /// The types of the operations are generated regardless of `skip_paths`.
+    /// Only applies to the TypeScript targets.
+    pub client: bool,
+    /// What the definitions are generated as.
+    /// The types of the client are TypeScript types regardless.
+    pub target: Target,
+    /// The package of the Go target, `models` if unspecified.
+    pub go_package: Option<String>,
+    /// The layout of the generated code.
+    pub format: Format,
+    /// The types formatted schemas are generated as.
+    pub format_mappings: FormatMappings,
+    /// Schemas with an `x-brand`, or strings of one of [`BRANDED_FORMATS`], are branded types,
+    /// e.g. `Uuid` declared as `string & { readonly __brand: "Uuid" }`, so that they aren't interchangeable.
+    /// Only applies to the TypeScript targets.
+    pub branded_types: bool,
+}
+
+/// The generated code and what had to be changed to generate it.
+#[derive(Clone, Debug)]
+pub struct Output {
+    /// The contents of the files, one after the other.
+    pub code: String,
+    pub files: Vec<backend::File>,
+    /// Definitions whose name isn't a valid type name, with the name they were given instead.
+    pub renames: Vec<(String, String)>,
+}
+
+pub fn use_spec(spec: &OpenApi, options: &Options) -> Result<String, Error> {
+    generate(spec, options).map(|output| output.code)
+}
+
+impl Options {
+    /// The backend of the target.
+    pub fn backend(&self) -> Box<dyn Backend> {
+        match self.target {
+            Target::TypeScript => Box::new(backend::TypeScript {
+                format: self.format.clone(),
+                client: self.client,
+            }),
+            Target::Zod => Box::new(backend::Zod {
+                format: self.format.clone(),
+                client: self.client,
+            }),
+            Target::Rust => Box::new(backend::Rust),
+            Target::TypedDict => Box::new(backend::Python {
+                classes: Classes::TypedDict,
+            }),
+            Target::Pydantic => Box::new(backend::Python {
+                classes: Classes::Pydantic,
+            }),
+            Target::Go => Box::new(backend::Go {
+                package: self
+                    .go_package
+                    .clone()
+                    .unwrap_or_else(|| backend::Go::default().package),
+            }),
+        }
+    }
+}
+
+/// The type name of every named schema, none of which is one of `reserved`.
+fn type_names(spec: &OpenApi, reserved: &[&str]) -> IndexMap<String, String> {
+    let keys = match spec {
+        OpenApi::V2(spec) => spec.definitions.iter().flatten().map(|(k, _)| k).collect(),
+        OpenApi::V3(spec) | OpenApi::V31(spec) => spec
+            .components
+            .iter()
+            .flat_map(|c| c.schemas.iter().flatten())
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>(),
     };
-    format!(
-        "// This file was generated using https://crates.io/crates/lupinas-lullaby\n{}",
-        types
-            .into_iter()
-            .filter_map(|(name, tt)| {
-                if skip_empty {
-                    filter_empty_types(&tt).map(|tt| (name, tt))
-                } else {
-                    Some((name, tt))
-                }
+    naming::type_names(keys.into_iter().map(String::as_str), reserved)
+}
+
+fn parse_schemas(
+    spec: &OpenApi,
+    names: &IndexMap<String, String>,
+    formats: &FormatMappings,
+    brands: bool,
+) -> Result<Vec<ir::Definition>, Error> {
+    match spec {
+        OpenApi::V2(spec) => use_spec2(spec, names, formats, brands),
+        OpenApi::V3(spec) | OpenApi::V31(spec) => use_spec3(spec, names, formats, brands),
+    }
+}
+
+/// The named schemas of the specification in the representation every target is derived from,
+/// before any of the [`Options`] besides the format mappings and the brands is applied.
+pub fn schemas(
+    spec: &OpenApi,
+    formats: &FormatMappings,
+    brands: bool,
+) -> Result<Vec<ir::Definition>, Error> {
+    parse_schemas(spec, &type_names(spec, &[]), formats, brands)
+}
+
+pub fn generate(spec: &OpenApi, options: &Options) -> Result<Output, Error> {
+    generate_with(spec, options, options.backend().as_ref())
+}
+
+/// Generates the definitions with `backend` instead of the backend of the target.
+/// The target, the format, the client and the package only apply to the built-in backends.
+pub fn generate_with(
+    spec: &OpenApi,
+    options: &Options,
+    backend: &dyn Backend,
+) -> Result<Output, Error> {
+    let brands = options.branded_types && backend.brands();
+    // The brands of the formats are declared whether or not a schema is named after them.
+    let mut reserved = backend.reserved().to_vec();
+    if brands {
+        reserved.extend(BRANDED_FORMATS.iter().map(|(_, brand)| *brand));
+    }
+    let names = type_names(spec, &reserved);
+    let renames = names
+        .iter()
+        .filter(|(key, name)| key != name)
+        .map(|(key, name)| (key.clone(), name.clone()))
+        .collect();
+    let mut types = parse_schemas(spec, &names, &options.format_mappings, brands)?;
+    let mut operations = vec![];
+    if !options.skip_paths || backend.operations() {
+        operations = match spec {
+            OpenApi::V2(spec) => use_spec2_paths(spec, &names, &options.format_mappings, brands)?,
+            OpenApi::V3(spec) | OpenApi::V31(spec) => {
+                use_spec3_paths(spec, &names, &options.format_mappings, brands)?
+            }
+        };
+        name_operations(&mut operations, &types, backend.operation_suffixes());
+        types.extend(operations.iter().flat_map(Operation::definitions));
+    }
+    let skip_types = options
+        .skip_type_names
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let definitions = types
+        .into_iter()
+        .filter_map(|definition| {
+            if options.skip_empty_types {
+                filter_empty_types(&definition.schema).map(|schema| ir::Definition {
+                    schema,
+                    ..definition
+                })
+            } else {
+                Some(definition)
+            }
+        })
+        .filter_map(|definition| {
+            filter_unwanted_types(&definition.schema, &skip_types).map(|schema| ir::Definition {
+                schema,
+                ..definition
             })
-            .filter_map(|(name, tt)| filter_unwanted_types(&tt, &skip_types).map(|tt| (name, tt)))
-            .map(|(name, jtype)| format!("export type {} = {};", name, jtype))
-            .collect::<Vec<String>>()
-            .join("\n")
-    )
+        })
+        .collect::<Vec<_>>();
+    let mut definitions = if options.hoist_nested_types || backend.hoist() {
+        hoist_nested_types(definitions)
+    } else {
+        definitions
+    };
+    definitions.sort_by(|l, r| l.name.cmp(&r.name));
+    let definitions = definitions
+        .into_iter()
+        .map(|definition| {
+            if options.allow_additional_properties {
+                ir::Definition {
+                    schema: allow_additional_properties(&definition.schema),
+                    ..definition
+                }
+            } else {
+                definition
+            }
+        })
+        .map(|definition| {
+            if options.sort_properties {
+                ir::Definition {
+                    schema: sort_properties(&definition.schema),
+                    ..definition
+                }
+            } else {
+                definition
+            }
+        })
+        .collect::<Vec<_>>();
+    let files = backend.emit(&Types {
+        definitions: &definitions,
+        operations: &operations,
+    });
+    let code = files
+        .iter()
+        .map(|file| file.contents.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok(Output {
+        code,
+        files,
+        renames,
+    })
 }
 
 #[test]
@@ -65,8 +349,8 @@ pub fn test_v2_json_examples() {
         .unwrap()
         .map(|res| res.unwrap().path())
         .filter(|path| path.is_file())
-        .map(|x| from_path(x))
-        .map(|spec| use_spec(&spec, false, vec![]))
+        .map(|x| from_path(x).unwrap())
+        .map(|spec| use_spec(&spec, &Options::default()).unwrap())
         .collect::<Vec<_>>();
 }
 
@@ -76,8 +360,8 @@ pub fn test_v2_yaml_examples() {
         .unwrap()
         .map(|res| res.unwrap().path())
         .filter(|path| path.is_file())
-        .map(|x| from_path(x))
-        .map(|spec| use_spec(&spec, false, vec![]))
+        .map(|x| from_path(x).unwrap())
+        .map(|spec| use_spec(&spec, &Options::default()).unwrap())
         .collect::<Vec<_>>();
 }
 
@@ -87,7 +371,1614 @@ pub fn test_v3_examples() {
         .unwrap()
         .map(|res| res.unwrap().path())
         .filter(|path| path.is_file())
-        .map(|x| from_path(x))
-        .map(|spec| use_spec(&spec, false, vec![]))
+        .map(|x| from_path(x).unwrap())
+        .map(|spec| use_spec(&spec, &Options::default()).unwrap())
         .collect::<Vec<_>>();
 }
+
+#[test]
+pub fn test_v31_examples() {
+    let _result = std::fs::read_dir("./data/v3.1")
+        .unwrap()
+        .map(|res| res.unwrap().path())
+        .filter(|path| path.is_file())
+        .map(|x| from_path(x).unwrap())
+        .map(|spec| use_spec(&spec, &Options::default()).unwrap())
+        .collect::<Vec<_>>();
+}
+
+#[test]
+pub fn test_json_schema_2020_12() {
+    for path in &[
+        "./data/v3.1/json-schema-example.json",
+        "./data/v3.1/json-schema-example.yaml",
+    ] {
+        let spec = from_path(path).unwrap();
+        assert!(matches!(spec, OpenApi::V31(_)));
+        let result = use_spec(&spec, &Options::default()).unwrap();
+        assert!(result.contains("kind: \"pet\";"));
+        assert!(result.contains("nickname?: string | null;"));
+        assert!(result.contains("location?: Coordinates;"));
+        assert!(result.contains("owner?: Owner & {"));
+        assert!(result.contains("export type Coordinates = [number, number];"));
+        assert!(result.contains("export type Tags = [string, ...number[]];"));
+    }
+}
+
+#[test]
+pub fn test_v3_composition() {
+    for path in &[
+        "./data/v3.0/composition-example.json",
+        "./data/v3.0/composition-example.yaml",
+    ] {
+        let result = use_spec(&from_path(path).unwrap(), &Options::default()).unwrap();
+        assert!(result.contains("export type AnyPet = Cat | Dog;"));
+        assert!(result.contains("export type Cat = Pet & {"));
+        assert!(result.contains("export type Identifier = string | number;"));
+        assert!(result.contains("} & (Cat | Dog);"));
+        assert!(result.contains("export type NotAString = unknown /* not string */;"));
+    }
+}
+
+#[test]
+pub fn test_additional_properties() {
+    let spec = from_path("./data/v3.0/additional-properties-example.yaml").unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("export type PetsByName = Record<string, Pet>;"));
+    assert!(result.contains("export type Labels = Record<string, any>;"));
+    assert!(result.contains("  [key: string]: number;"));
+    assert!(!result.contains("unknown"));
+    let options = Options {
+        allow_additional_properties: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type Pet = {\n  name: string;\n  [key: string]: unknown;\n};"));
+    assert_eq!(result.matches("[key: string]: unknown;").count(), 1);
+
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Headers:
+    type: object
+    properties:
+      host:
+        type: boolean
+    additionalProperties:
+      type: string
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("  [key: string]: string | boolean | undefined;"));
+}
+
+#[test]
+pub fn test_nullable() {
+    let spec = from_path("./data/v3.0/nullable-example.yaml").unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("name: string | null;"));
+    assert!(result.contains("nickname?: string | null;"));
+    assert!(result.contains("owner: Owner | null;"));
+    assert!(result.contains("tags?: (string | null)[];"));
+    assert!(result.contains("status?: \"available\" | \"sold\" | null;"));
+
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Pet:
+    type: object
+    required:
+      - name
+    properties:
+      name:
+        type: string
+        x-nullable: true
+      owner:
+        $ref: "#/definitions/Pet"
+        x-nullable: true
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("name: string | null;"));
+    assert!(result.contains("owner?: Pet | null;"));
+}
+
+#[test]
+pub fn test_discriminator() {
+    let spec = from_path("./data/v3.0/discriminator-example.yaml").unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("petType: \"cat\" | \"kitten\";"));
+    assert!(result.contains("petType: \"Dog\";"));
+    assert!(result.contains("kind: \"round\";"));
+    assert!(result.contains("kind: \"Square\";"));
+
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Pet:
+    type: object
+    discriminator: petType
+    required:
+      - petType
+    properties:
+      petType:
+        type: string
+  Cat:
+    allOf:
+      - $ref: "#/definitions/Pet"
+      - properties:
+          livesLeft:
+            type: integer
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("export type Cat = Pet & {"));
+    assert!(result.contains("petType: \"Cat\";"));
+}
+
+#[test]
+pub fn test_documentation() {
+    let spec = from_path("./data/v3.0/documentation-example.yaml").unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("/**\n * Pet\n *\n * A pet of the store.\n * Every pet has a name.\n */\nexport type Pet = {"));
+    assert!(result.contains("   * The name given by the owner.\n   * @example \"Rex\"\n"));
+    assert!(result.contains("   * @pattern ^[A-Z]\n   */\n  name: string;"));
+    assert!(result.contains(
+        "   * @default 1\n   * @format int32\n   * @minimum 0\n   * @exclusiveMaximum 100\n"
+    ));
+    assert!(result.contains("   * @maxItems 10\n   * @uniqueItems true\n"));
+    assert!(
+        result.contains("   * Replaced by the name, do not use *\\/ anymore.\n   * @deprecated\n")
+    );
+
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Pet:
+    description: A pet of the store.
+    properties:
+      name:
+        type: string
+        default: Rex
+      age:
+        type: integer
+        minimum: 0
+        exclusiveMinimum: true
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("/**\n * A pet of the store.\n */\nexport type Pet = {"));
+    assert!(result.contains("   * @default \"Rex\"\n"));
+    assert!(result.contains("   * @exclusiveMinimum 0\n"));
+}
+
+#[test]
+pub fn test_deterministic_output() {
+    let options = [
+        Options::default(),
+        Options {
+            allow_additional_properties: true,
+            sort_properties: true,
+            ..Options::default()
+        },
+    ];
+    for version in ["v2.0/json", "v2.0/yaml", "v3.0", "v3.1"] {
+        for path in std::fs::read_dir(format!("./data/{}", version))
+            .unwrap()
+            .map(|res| res.unwrap().path())
+            .filter(|path| path.is_file())
+        {
+            for options in options.iter() {
+                let first = use_spec(&from_path(&path).unwrap(), options).unwrap();
+                let second = use_spec(&from_path(&path).unwrap(), options).unwrap();
+                assert_eq!(first, second, "{}", path.display());
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_property_order() {
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+        age:
+          type: integer
+        breed:
+          type: string
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("name?: string;\n  age?: number;\n  breed?: string;"));
+    let options = Options {
+        sort_properties: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("age?: number;\n  breed?: string;\n  name?: string;"));
+}
+
+#[test]
+pub fn test_format() {
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+components:
+  schemas:
+    Pet:
+      type: object
+      required:
+        - x-id
+      properties:
+        status:
+          type: string
+          enum: [available, pending, sold, reserved, quarantined, adopted, returned]
+        x-id:
+          type: string
+        owner:
+          nullable: true
+          type: object
+          properties:
+            name:
+              type: string
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result
+        .contains("export type Pet = {\n  status?:\n    | \"available\"\n    | \"pending\"\n"));
+    assert!(result.contains("    | \"returned\";\n  \"x-id\": string;\n"));
+    assert!(result.contains("  owner?: {\n    name?: string;\n  } | null;\n};"));
+
+    let options = Options {
+        format: Format {
+            indent: "\t".to_string(),
+            quote: Quote::Single,
+            semicolons: false,
+            line_width: 200,
+            ..Format::default()
+        },
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("\tstatus?: 'available' | 'pending' | 'sold' | "));
+    assert!(result.contains("'returned'\n\t'x-id': string\n"));
+    assert!(result.contains("\towner?: {\n\t\tname?: string\n\t} | null\n}"));
+}
+
+#[test]
+pub fn test_interfaces() {
+    let options = Options {
+        format: Format {
+            interfaces: true,
+            ..Format::default()
+        },
+        ..Options::default()
+    };
+    let spec = from_path("./data/v3.0/composition-example.yaml").unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export interface Pet {\n  name: string;\n}"));
+    assert!(result.contains("export interface Cat extends Pet {\n  hunts?: boolean;\n}"));
+    assert!(result.contains("export type AnyPet = Cat | Dog;"));
+    assert!(result.contains("export type NamedPet = {"));
+
+    let spec = from_path("./data/v3.0/discriminator-example.yaml").unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains(
+        "export interface Dog extends Pet {\n  barks?: boolean;\n  petType: \"Dog\";\n}"
+    ));
+
+    let spec = from_path("./data/v3.0/additional-properties-example.yaml").unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result
+        .contains("export interface Inventory {\n  total: number;\n  [key: string]: number;\n}"));
+    assert!(result.contains("export type PetsByName = Record<string, Pet>;"));
+}
+
+#[test]
+pub fn test_enums() {
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+components:
+  schemas:
+    Status:
+      type: string
+      enum: [available, in-progress, SOLD_OUT, 1st, "", available!]
+    Level:
+      type: string
+      enum: [low, high]
+      x-enum-varnames: [LOW, HIGH]
+    Size:
+      type: string
+      enum: [s, m]
+      x-enumNames: [small one, medium one]
+"##,
+    )
+    .unwrap();
+    let options = |enums| Options {
+        format: Format {
+            enums,
+            ..Format::default()
+        },
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options(Enums::Union)).unwrap();
+    assert!(result.contains("export type Level = \"low\" | \"high\";"));
+
+    let result = use_spec(&spec, &options(Enums::Enum)).unwrap();
+    assert!(result.contains("export enum Level {\n  LOW = \"low\",\n  HIGH = \"high\",\n}"));
+    assert!(result.contains("export enum Size {\n  SmallOne = \"s\",\n  MediumOne = \"m\",\n}"));
+    assert!(result.contains(
+        "  Available = \"available\",\n  InProgress = \"in-progress\",\n  SoldOut = \"SOLD_OUT\",\n"
+    ));
+    assert!(result.contains("  _1st = \"1st\",\n  Empty = \"\",\n  Available2 = \"available!\",\n"));
+
+    let result = use_spec(&spec, &options(Enums::Const)).unwrap();
+    assert!(result.contains(
+        "export const Level = [\"low\", \"high\"] as const;\nexport type Level = (typeof Level)[number];"
+    ));
+}
+
+#[test]
+pub fn test_hoist_nested_types() {
+    let spec = from_path("./data/v3.0/nested-types-example.yaml").unwrap();
+    let options = Options {
+        hoist_nested_types: true,
+        format: Format {
+            enums: Enums::Enum,
+            ..Format::default()
+        },
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("  status?: OrderStatus2;\n"));
+    assert!(result.contains("  shipping?: OrderShipping;\n  billing?: OrderShipping;\n"));
+    assert!(result.contains("  lines?: OrderLines[];\n"));
+    assert!(result.contains("  customer?: CustomerInfo;\n"));
+    assert!(result.contains("  previous?: OrderStatus2 | null;\n"));
+    assert!(result.contains(
+        "export type OrderShipping = {\n  street?: string;\n  country?: OrderShippingCountry;\n};"
+    ));
+    assert!(result.contains("export type OrderShippingCountry = {\n  code?: string;\n};"));
+    assert!(result.contains("export type OrderStatus = string;"));
+    assert!(result.contains("export enum OrderStatus2 {\n  PLACED = \"placed\","));
+    assert!(!result.contains("OrderBilling"));
+
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(!result.contains("OrderShipping"));
+}
+
+#[test]
+pub fn test_naming() {
+    let spec = from_path("./data/v3.0/naming-example.yaml").unwrap();
+    let output = generate(&spec, &Options::default()).unwrap();
+    assert_eq!(
+        output.renames,
+        [
+            ("Date", "Date2"),
+            ("Foo[Bar]", "FooBar"),
+            ("Pet-Response", "PetResponse2"),
+            ("pet", "Pet"),
+            ("v1.User", "V1User"),
+        ]
+        .iter()
+        .map(|(k, n)| (k.to_string(), n.to_string()))
+        .collect::<Vec<_>>()
+    );
+    let result = output.code;
+    assert!(result.contains("export type Date2 = string;"));
+    assert!(result.contains("export type FooBar = string[];"));
+    assert!(result.contains("export type PetResponse = {\n  pet?: Pet;\n};\nexport type PetResponse2 = {\n  user?: V1User;\n  wrapped?: FooBar;\n  date?: Date2;\n"));
+    assert!(result.contains("  created?: Date;\n"));
+    assert!(result.contains("export type Pet = {"));
+    assert!(result.contains("export type V1User = {"));
+
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  pet-owner:
+    type: object
+    properties:
+      pet:
+        $ref: "#/definitions/v2.Pet"
+  v2.Pet:
+    type: object
+    properties:
+      name:
+        type: string
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("export type PetOwner = {\n  pet?: V2Pet;\n};"));
+    assert!(result.contains("export type V2Pet = {"));
+}
+
+#[test]
+pub fn test_escaping() {
+    let spec = from_path("./data/v3.0/escaping-example.yaml").unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains(
+        r#"export type Quotes = "it's" | "say \"hi\"" | "back\\slash" | "`${template}`";"#
+    ));
+    assert!(result.contains(r#"  | "line\nbreak""#));
+    assert!(result.contains(r#"  | "carriage\rreturn""#));
+    assert!(result.contains(r#"  | "tab\tstop""#));
+    assert!(result.contains(r#"  | "line\u2028separator""#));
+    assert!(result.contains(r#"  | "paragraph\u2029separator""#));
+    assert!(result.contains(r#"  | "nul\u0000byte";"#));
+    assert!(result.contains(r#"  "it's": string;"#));
+    assert!(result.contains(r#"  "say \"hi\""?: string;"#));
+    assert!(result.contains(r#"  "new\nline"?: string;"#));
+    assert!(result.contains(r#"  "back\\slash"?: string;"#));
+    assert!(result.contains(" * Ends the comment *\\/ early.\n"));
+    assert!(result.contains(r#" * @default "*\/ \"quoted\"\n""#));
+
+    let options = Options {
+        format: Format {
+            quote: Quote::Single,
+            enums: Enums::Enum,
+            ..Format::default()
+        },
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains(r#"  ItS = 'it\'s',"#));
+    assert!(result.contains(r#"  SayHi = 'say "hi"',"#));
+    assert!(result.contains(r#"  LineSeparator = 'line\u2028separator',"#));
+    assert!(result.contains(r#"  'it\'s': string;"#));
+    assert!(result.contains(r#"  'new\nline'?: string;"#));
+}
+
+#[test]
+pub fn test_paths() {
+    let spec = from_path("./data/v3.0/paths-example.yaml").unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    // Operation parameters override the ones of the path, cookies are left out.
+    assert!(result.contains("export type GetPetPathParams = {\n  /**\n   * The id of the pet, as a number\n   */\n  petId: number;\n};"));
+    assert!(result.contains("export type GetPetQueryParams = {\n  fields: string[];\n};"));
+    assert!(result.contains("export type GetPetHeaders = {\n  \"X-Request-Id\"?: string;\n};"));
+    assert!(!result.contains("session"));
+    // The JSON media type is preferred.
+    assert!(result.contains(" * The pet\n */\nexport type GetPetResponse200 = Pet;"));
+    assert!(result.contains("export type GetPetResponse4XX = {\n  title?: string;\n};"));
+    // Operations without an operationId are named after their method and path.
+    assert!(result.contains("export type DeletePetsPetIdPathParams = {"));
+    assert!(!result.contains("DeletePetsPetIdResponse204"));
+    assert!(result.contains(" * The pet to create\n */\nexport type CreatePetRequestBody = Pet;"));
+    assert!(result.contains("export type CreatePetResponse201 = string;"));
+    // Operations whose types collide with a definition are suffixed.
+    assert!(result.contains("export type UploadPetRequestBody = string;"));
+    assert!(result.contains("export type UploadPet2RequestBody = any;"));
+
+    let options = Options {
+        skip_paths: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type Pet = {"));
+    assert!(!result.contains("GetPet"));
+
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+paths:
+  /pets/{id}:
+    parameters:
+      - name: id
+        in: path
+        type: integer
+        format: int64
+    put:
+      operationId: updatePet
+      parameters:
+        - name: pet
+          in: body
+          required: true
+          schema:
+            $ref: "#/definitions/Pet"
+        - name: status
+          in: query
+          type: string
+          enum:
+            - available
+            - sold
+      responses:
+        200:
+          description: The updated pet
+          schema:
+            $ref: "#/definitions/Pet"
+        204:
+          description: Nothing changed
+    post:
+      parameters:
+        - name: name
+          in: formData
+          required: true
+          type: string
+        - name: photo
+          in: formData
+          type: file
+      responses:
+        default:
+          description: Anything
+definitions:
+  Pet:
+    type: object
+    properties:
+      name:
+        type: string
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains(
+        "export type UpdatePetPathParams = {\n  /**\n   * @format int64\n   */\n  id: number;\n};"
+    ));
+    assert!(result.contains(
+        "export type UpdatePetQueryParams = {\n  status?: \"available\" | \"sold\";\n};"
+    ));
+    assert!(result.contains("export type UpdatePetRequestBody = Pet;"));
+    assert!(result.contains(" * The updated pet\n */\nexport type UpdatePetResponse200 = Pet;"));
+    assert!(!result.contains("UpdatePetResponse204"));
+    assert!(result.contains("export type PostPetsIdPathParams = {"));
+    assert!(result
+        .contains("export type PostPetsIdRequestBody = {\n  name: string;\n  photo?: any;\n};"));
+}
+
+#[test]
+pub fn test_paths_enum_parameters() {
+    // Swagger 2 enums aren't only strings, in parameters as in their items.
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      parameters:
+        - name: limit
+          in: query
+          type: integer
+          enum: [10, 20, 50]
+        - name: ids
+          in: query
+          type: array
+          items:
+            type: integer
+            enum: [1, 2, 3]
+      responses:
+        200:
+          description: The pets
+definitions:
+  Pet:
+    type: object
+    properties:
+      name:
+        type: string
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result
+        .contains("export type ListPetsQueryParams = {\n  limit?: number;\n  ids?: number[];\n};"));
+
+    let options = Options {
+        skip_paths: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type Pet = {"));
+}
+
+#[test]
+pub fn test_skip_paths_invalid() {
+    // The paths are only read when their operations are generated.
+    let spec = from_bytes(
+        br##"
+openapi: 3.0.0
+paths:
+  /pets:
+    get:
+      parameters: not a list
+      responses:
+        200:
+          description: The pets
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+"##,
+    )
+    .unwrap();
+    assert!(use_spec(&spec, &Options::default()).is_err());
+
+    let options = Options {
+        skip_paths: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type Pet = {"));
+}
+
+#[test]
+pub fn test_client() {
+    let spec = from_path("./data/v3.0/paths-example.yaml").unwrap();
+    let options = Options {
+        client: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type ClientOptions = {\n"));
+    assert!(result.contains("export type GetPetRequest = {\n  path: GetPetPathParams;\n  query: GetPetQueryParams;\n  headers?: GetPetHeaders;\n};"));
+    assert!(result.contains("export type GetPetResult =\n  | {\n    status: 200;\n    data: GetPetResponse200;\n    response: Response;\n  }\n  | {\n    status: number;\n    data: GetPetResponse4XX;\n    response: Response;\n  };"));
+    assert!(result.contains("export type DeletePetsPetIdResult = {\n  status: 204;\n  data: undefined;\n  response: Response;\n};"));
+    assert!(result
+        .contains("export function createClient(options: ClientOptions = {}) {\n  return {\n"));
+    assert!(result.contains("    async getPet(request: GetPetRequest): Promise<GetPetResult> {\n      return send(\n        options,\n        \"GET\",\n        `/pets/${serializePath(request.path.petId)}`,\n        request,\n      );\n    },\n"));
+    assert!(result.contains("    /**\n     * Creates a pet\n     */\n    async createPet(request: CreatePetRequest): Promise<CreatePetResult> {\n      return send(options, \"POST\", `/pets`, request, {}, \"application/json\");\n    },\n"));
+    assert!(result.contains("    async uploadPet2(\n      request: UploadPet2Request = {},\n    ): Promise<UploadPet2Result> {\n"));
+    assert!(result.ends_with("  };\n}"));
+
+    // The client generates the types of the operations regardless, and follows the format.
+    let options = Options {
+        client: true,
+        skip_paths: true,
+        format: Format {
+            quote: Quote::Single,
+            semicolons: false,
+            ..Format::default()
+        },
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type GetPetQueryParams = {"));
+    assert!(result.contains("  const essence = mediaType.split(';')[0].trim().toLowerCase()\n"));
+    assert!(result.contains(
+        "      return send(options, 'POST', `/pets`, request, {}, 'application/json')\n"
+    ));
+    assert!(!result.contains('"'));
+
+    // Swagger 2 arrays are comma separated unless their `collectionFormat` says otherwise.
+    let spec = from_path("./data/v2.0/yaml/petstore-expanded.yaml").unwrap();
+    let client = Options {
+        client: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &client).unwrap();
+    assert!(result.contains(
+        "      return send(options, \"GET\", `/pets`, request, { tags: [\"form\", false] });\n"
+    ));
+
+    // Definitions can't shadow the types the client refers to.
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+paths:
+  /things:
+    get:
+      operationId: listThings
+      parameters:
+        - name: tags
+          in: query
+          style: pipeDelimited
+          explode: false
+          schema:
+            type: array
+            items:
+              type: string
+      responses:
+        200:
+          description: The things
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Response"
+components:
+  schemas:
+    Response:
+      type: string
+    ListThingsResult:
+      type: string
+"##,
+    )
+    .unwrap();
+    let output = generate(&spec, &options).unwrap();
+    assert_eq!(
+        output.renames,
+        vec![("Response".to_string(), "Response2".to_string())]
+    );
+    assert!(output
+        .code
+        .contains("export type ListThings2Response200 = Response2"));
+    assert!(output
+        .code
+        .contains("request: ListThings2Request = {},\n    ): Promise<ListThings2Result> {"));
+    assert!(output
+        .code
+        .contains("`/things`,\n        request,\n        { tags: ['pipeDelimited', false] },\n"));
+}
+
+#[test]
+pub fn test_zod() {
+    let spec = from_path("./data/v3.0/recursive-example.yaml").unwrap();
+    let options = Options {
+        target: Target::Zod,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.starts_with("// This file was generated using https://crates.io/crates/lupinas-lullaby\nimport { z } from \"zod\";\n\n"));
+    assert!(result.contains("export const Kind = z.enum([\"text\", \"binary\"]);\nexport type Kind = z.infer<typeof Kind>;"));
+    assert!(result.contains("export const File = z.object({\n  name: z.string(),\n  kind: Kind.optional(),\n  size: z.number().nullable().optional(),\n  labels: z.record(z.string()).optional(),\n  /**\n   * @format date-time\n   */\n  modified: z.coerce.date().optional(),\n});\nexport type File = z.infer<typeof File>;"));
+    assert!(result.contains("export const Named = z.intersection(\n  File,\n  z.intersection(\n    z.object({\n      title: z.string().optional(),\n    }),\n    z.object({\n      version: z.number().optional(),\n    }).strict(),\n  ),\n);"));
+
+    // Recursive definitions declare their type and refer to the schemas that aren't declared yet lazily.
+    assert!(result.contains("export type Category = {\n  name: string;\n  parent?: Category;\n  children?: Category[];\n};\nexport const Category: z.ZodType<Category> = z.object({\n  name: z.string(),\n  parent: z.lazy(() => Category).optional(),\n  children: z.array(z.lazy(() => Category)).optional(),\n});"));
+    assert!(result
+        .contains("export const Entry: z.ZodType<Entry> = z.union([z.lazy(() => Folder), File]);"));
+    assert!(result.contains("  entries: z.array(Entry).optional(),\n"));
+    // Schemas are declared after the ones they refer to.
+    assert!(result.find("export const Kind").unwrap() < result.find("export const File").unwrap());
+    assert!(result.find("export const File").unwrap() < result.find("export const Entry").unwrap());
+}
+
+#[test]
+pub fn test_rust() {
+    let spec = from_path("./data/v3.0/discriminator-example.yaml").unwrap();
+    let options = Options {
+        target: Target::Rust,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.starts_with("// This file was generated using https://crates.io/crates/lupinas-lullaby\nuse serde::{Deserialize, Serialize};\n\n#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]\npub struct Cat {\n"));
+    // The discriminator is left to the union, and to the struct it is inherited from.
+    assert!(result.contains("#[serde(tag = \"kind\")]\npub enum Shape {\n    #[serde(rename = \"round\")]\n    Circle(Circle),\n    Square(Square),\n}"));
+    assert!(result.contains("pub struct Circle {\n    pub radius: f64,\n}"));
+    assert!(result.contains("pub struct Cat {\n    #[serde(flatten)]\n    pub pet: Pet,\n    #[serde(rename = \"livesLeft\", default, skip_serializing_if = \"Option::is_none\")]\n    pub lives_left: Option<i64>,\n}"));
+
+    let spec = from_path("./data/v3.0/recursive-example.yaml").unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(
+        result.contains("use serde::{Deserialize, Serialize};\nuse std::collections::HashMap;\n")
+    );
+    assert!(result.contains("    pub parent: Option<Box<Category>>,\n"));
+    assert!(result.contains("    pub children: Option<Vec<Category>>,\n"));
+    assert!(result.contains(
+        "#[serde(untagged)]\npub enum Entry {\n    Folder(Box<Folder>),\n    File(File),\n}"
+    ));
+    assert!(result.contains("    pub labels: Option<HashMap<String, String>>,\n"));
+    assert!(result.contains("pub enum Kind {\n    #[serde(rename = \"text\")]\n    Text,\n    #[serde(rename = \"binary\")]\n    Binary,\n}"));
+
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+info:
+  version: 1.0.0
+  title: Rust Example
+paths: {}
+components:
+  schemas:
+    Token:
+      type: object
+      additionalProperties: false
+      required:
+        - type
+        - X-Request-ID
+        - expires
+      properties:
+        type:
+          type: string
+        self:
+          type: string
+        X-Request-ID:
+          type: integer
+          format: int64
+        expires:
+          type: string
+          nullable: true
+        scope:
+          type: object
+          properties:
+            read:
+              type: boolean
+        ids:
+          type: array
+          items:
+            type: integer
+            format: int32
+    Counts:
+      type: object
+      additionalProperties:
+        type: integer
+    Labels:
+      type: object
+      properties:
+        name:
+          type: string
+      additionalProperties:
+        type: string
+    Id:
+      oneOf:
+        - type: string
+        - type: integer
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("#[serde(deny_unknown_fields)]\npub struct Token {\n    pub r#type: String,\n    #[serde(rename = \"self\", default, skip_serializing_if = \"Option::is_none\")]\n    pub self_: Option<String>,\n    #[serde(rename = \"X-Request-ID\")]\n    pub x_request_id: i64,\n    pub expires: Option<String>,\n"));
+    // Nested objects are always declared as types of their own.
+    assert!(result.contains("    pub scope: Option<TokenScope>,\n"));
+    assert!(result.contains("pub struct TokenScope {\n"));
+    // Integers are sized after their own format, wherever they are.
+    assert!(result.contains("    pub ids: Option<Vec<i32>>,\n"));
+    assert!(result.contains("pub type Counts = HashMap<String, i64>;"));
+    assert!(result.contains("pub struct Labels {\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub name: Option<String>,\n    #[serde(flatten)]\n    pub additional_properties: HashMap<String, String>,\n}"));
+    assert!(result
+        .contains("#[serde(untagged)]\npub enum Id {\n    String(String),\n    Number(i64),\n}"));
+}
+
+#[test]
+pub fn test_python() {
+    let spec = from_path("./data/v3.0/recursive-example.yaml").unwrap();
+    let options = Options {
+        target: Target::TypedDict,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.starts_with("# This file was generated using https://crates.io/crates/lupinas-lullaby\nfrom typing import Dict, List, Literal, Optional, Union\nfrom typing_extensions import NotRequired, TypedDict\n\n\nclass Category(TypedDict):\n"));
+    // The definitions of a cycle refer to the ones which aren't declared yet by name.
+    assert!(result.contains(
+        "    parent: NotRequired[\"Category\"]\n    children: NotRequired[List[\"Category\"]]\n"
+    ));
+    assert!(result.contains("\n\n\nEntry = Union[\"Folder\", File]\n\n\nclass Folder(TypedDict):\n    entries: NotRequired[List[Entry]]\n"));
+    assert!(result.contains("Kind = Literal[\"text\", \"binary\"]"));
+    assert!(result.contains(
+        "    size: NotRequired[Optional[int]]\n    labels: NotRequired[Dict[str, str]]\n"
+    ));
+    assert!(result.contains("class Named(File):\n    title: NotRequired[str]\n"));
+    // Definitions are declared after the ones they refer to.
+    assert!(result.find("class File").unwrap() < result.find("Entry =").unwrap());
+
+    let options = Options {
+        target: Target::Pydantic,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("from pydantic import BaseModel, ConfigDict\n"));
+    assert!(result.contains("class Category(BaseModel):\n    name: str\n    parent: Optional[\"Category\"] = None\n    children: Optional[List[\"Category\"]] = None\n\n\nCategory.model_rebuild()"));
+    assert!(result.contains("class Folder(BaseModel):\n    entries: Optional[List[Entry]] = None\n\n\nFolder.model_rebuild()"));
+    assert!(
+        result.contains("class Named(File):\n    model_config = ConfigDict(extra=\"forbid\")\n")
+    );
+    assert!(result.contains("    size: Optional[int] = None\n"));
+    assert!(result.ends_with("    version: Optional[int] = None"));
+
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+info:
+  version: 1.0.0
+  title: Python Example
+paths: {}
+components:
+  schemas:
+    Token:
+      type: object
+      description: A token.
+      required:
+        - X-Request-ID
+        - expires
+      properties:
+        class:
+          type: string
+          description: The class of the token.
+        X-Request-ID:
+          type: integer
+          format: int64
+        expires:
+          type: string
+          nullable: true
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("class Token(BaseModel):\n    \"\"\"A token.\"\"\"\n    class_: Optional[str] = Field(None, alias=\"class\")\n    \"\"\"The class of the token.\"\"\"\n    x_request_id: int = Field(alias=\"X-Request-ID\")\n    expires: Optional[str]"));
+    let options = Options {
+        target: Target::TypedDict,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("Token = TypedDict(\"Token\", {\n    \"class\": NotRequired[str],\n    \"X-Request-ID\": int,\n    \"expires\": Optional[str],\n})\n\"\"\"A token.\"\"\""));
+}
+
+#[test]
+pub fn test_go() {
+    let spec = from_path("./data/v3.0/recursive-example.yaml").unwrap();
+    let options = Options {
+        target: Target::Go,
+        go_package: Some("files".to_string()),
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.starts_with("// This file was generated using https://crates.io/crates/lupinas-lullaby\n\npackage files\n\nimport \"encoding/json\"\n\ntype Category struct {\n"));
+    // A struct refers to itself through a pointer, and the fields are aligned as gofmt would.
+    assert!(result.contains("\tName     string     `json:\"name\"`\n\tParent   *Category  `json:\"parent,omitempty\"`\n\tChildren []Category `json:\"children,omitempty\"`\n"));
+    assert!(result.contains("\ntype Entry = json.RawMessage\n"));
+    assert!(result.contains("\tKind     *Kind             `json:\"kind,omitempty\"`\n\tSize     *int64            `json:\"size,omitempty\"`\n\tLabels   map[string]string `json:\"labels,omitempty\"`\n"));
+    assert!(result.contains("type Kind string\n\nconst (\n\tKindText   Kind = \"text\"\n\tKindBinary Kind = \"binary\"\n)"));
+    assert!(result
+        .contains("type Named struct {\n\tFile\n\tTitle   *string `json:\"title,omitempty\"`\n"));
+
+    let spec = from_path("./data/v3.0/nullable-example.yaml").unwrap();
+    let options = Options {
+        target: Target::Go,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("\npackage models\n"));
+    // Nullable properties are pointers even when required, unless they can be nil already.
+    assert!(result.contains("\tName          *string         `json:\"name\"`\n"));
+    assert!(result.contains("\tTags          []*string       `json:\"tags,omitempty\"`\n"));
+    assert!(result.contains("\tPreviousOwner json.RawMessage `json:\"previousOwner,omitempty\"`\n"));
+
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+info:
+  version: 1.0.0
+  title: Go Example
+paths: {}
+components:
+  schemas:
+    Page:
+      type: object
+      required:
+        - ids
+      properties:
+        ids:
+          type: array
+          items:
+            type: integer
+            format: int32
+        total:
+          type: integer
+        counts:
+          type: object
+          additionalProperties:
+            type: integer
+            format: int64
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &options).unwrap();
+    // Integers are sized after their own format, wherever they are.
+    assert!(result.contains("\tIds    []int32          `json:\"ids\"`\n\tTotal  *int64           `json:\"total,omitempty\"`\n\tCounts map[string]int64 `json:\"counts,omitempty\"`\n"));
+}
+
+#[test]
+pub fn test_backend() {
+    // One file per definition, listing the properties of objects.
+    struct Properties;
+
+    impl Backend for Properties {
+        fn reserved(&self) -> &[&'static str] {
+            &["Pet"]
+        }
+
+        fn hoist(&self) -> bool {
+            true
+        }
+
+        fn emit(&self, types: &Types) -> Vec<backend::File> {
+            types
+                .definitions
+                .iter()
+                .map(|definition| backend::File {
+                    path: format!("{}.txt", definition.name),
+                    contents: match &definition.schema.kind {
+                        ir::Kind::Object(fields, _) => {
+                            fields.keys().cloned().collect::<Vec<_>>().join("\n")
+                        }
+                        _ => JavaScriptType::from(&definition.schema).to_string(),
+                    },
+                })
+                .collect()
+        }
+    }
+
+    let spec = from_path("./data/v3.0/nested-types-example.yaml").unwrap();
+    let options = Options {
+        skip_paths: true,
+        ..Options::default()
+    };
+    let output = generate_with(&spec, &options, &Properties).unwrap();
+    assert_eq!(
+        output
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "CustomerInfo.txt",
+            "Order.txt",
+            "OrderLines.txt",
+            "OrderShipping.txt",
+            "OrderShippingCountry.txt",
+            "OrderStatus.txt",
+            "OrderStatus2.txt",
+        ]
+    );
+    assert_eq!(output.files[3].contents, "street\ncountry".to_string());
+    assert_eq!(output.files[5].contents, "string");
+
+    let spec = from_path("./data/v3.0/petstore.yaml").unwrap();
+    let output = generate_with(&spec, &options, &Properties).unwrap();
+    assert_eq!(output.renames, [("Pet".to_string(), "Pet2".to_string())]);
+    assert_eq!(output.files[1].path, "Pet2.txt");
+    assert_eq!(output.files[2].contents, "Pet2[]");
+
+    // The built-in targets are backends too.
+    let options = Options::default();
+    let output = generate_with(&spec, &options, &backend::TypeScript::default()).unwrap();
+    assert_eq!(output.files[0].path, "types.ts");
+    assert_eq!(output.code, use_spec(&spec, &options).unwrap());
+}
+
+#[test]
+pub fn test_schemas() {
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+info:
+  version: 1.0.0
+  title: Metadata Example
+paths: {}
+components:
+  schemas:
+    user:
+      type: object
+      required:
+        - id
+      properties:
+        id:
+          type: string
+          format: uuid
+          readOnly: true
+        password:
+          type: string
+          writeOnly: true
+          minLength: 8
+        tags:
+          type: array
+          items:
+            $ref: "#/components/schemas/user"
+        nickname:
+          type: string
+          nullable: true
+          description: What friends call the user.
+"##,
+    )
+    .unwrap();
+    let schemas = schemas(&spec, &FormatMappings::default(), false).unwrap();
+    assert_eq!(schemas[0].name, "User");
+    let fields = match &schemas[0].schema.kind {
+        ir::Kind::Object(fields, _) => fields,
+        kind => panic!("expected an object, got {:?}", kind),
+    };
+    assert_eq!(schemas[0].schema.meta.pointer, "#/components/schemas/user");
+    let id = &fields["id"];
+    assert!(id.required && id.schema.meta.read_only);
+    assert!(matches!(id.schema.kind, ir::Kind::String));
+    assert_eq!(id.schema.meta.docs.format.as_deref(), Some("uuid"));
+    let password = &fields["password"].schema;
+    assert!(password.meta.write_only);
+    assert_eq!(
+        password.meta.docs.constraints,
+        [("minLength", "8".to_string())]
+    );
+    match &fields["tags"].schema.kind {
+        ir::Kind::Array(items) => {
+            assert!(matches!(&items.kind, ir::Kind::Reference(name) if name == "User"));
+            assert_eq!(
+                items.meta.pointer,
+                "#/components/schemas/user/properties/tags/items"
+            );
+        }
+        kind => panic!("expected an array, got {:?}", kind),
+    }
+    let nickname = &fields["nickname"].schema;
+    assert!(nickname.meta.nullable);
+    assert_eq!(
+        JavaScriptType::from(nickname).to_string(),
+        "string | null".to_string()
+    );
+}
+
+#[test]
+pub fn test_format_mappings() {
+    let mut format_mappings = FormatMappings::default();
+    format_mappings.insert("integer", "int64", "bigint");
+    format_mappings.insert("string", "date-time", "string");
+    format_mappings.insert("string", "uuid", "Uuid");
+    assert_eq!(
+        FormatMappings::parse("string:binary=Blob"),
+        Some(("string", "binary", "Blob"))
+    );
+    assert_eq!(FormatMappings::parse("string=Blob"), None);
+    assert_eq!(FormatMappings::parse("string:binary="), None);
+
+    let v3 = from_bytes(
+        br##"
+openapi: "3.0.0"
+info:
+  version: 1.0.0
+  title: Format Example
+paths: {}
+components:
+  schemas:
+    Order:
+      type: object
+      required:
+        - id
+      properties:
+        id:
+          type: string
+          format: uuid
+        total:
+          type: integer
+          format: int64
+        created:
+          type: string
+          format: date-time
+        shipped:
+          type: string
+          format: date
+        count:
+          type: integer
+          format: int32
+"##,
+    )
+    .unwrap();
+    let v2 = from_bytes(
+        br##"
+swagger: "2.0"
+info:
+  version: 1.0.0
+  title: Format Example
+paths: {}
+definitions:
+  Order:
+    type: object
+    required:
+      - id
+    properties:
+      id:
+        type: string
+        format: uuid
+      total:
+        type: integer
+        format: int64
+      created:
+        type: string
+        format: date-time
+      shipped:
+        type: string
+        format: date
+      count:
+        type: integer
+        format: int32
+"##,
+    )
+    .unwrap();
+    // Both versions map the formats the same way.
+    for spec in [&v3, &v2] {
+        let options = Options {
+            format_mappings: format_mappings.clone(),
+            ..Options::default()
+        };
+        let result = use_spec(spec, &options).unwrap();
+        assert!(result.contains("  id: Uuid;\n"));
+        assert!(result.contains("  total?: bigint;\n"));
+        assert!(result.contains("  created?: string;\n"));
+        assert!(result.contains("  shipped?: Date;\n"));
+        assert!(result.contains("  count?: number;\n"));
+
+        let result = use_spec(spec, &Options::default()).unwrap();
+        assert!(result.contains("  id: string;\n"));
+        assert!(result.contains("  total?: number;\n"));
+        assert!(result.contains("  created?: Date;\n"));
+
+        let options = Options {
+            target: Target::Zod,
+            format_mappings: format_mappings.clone(),
+            ..Options::default()
+        };
+        let result = use_spec(spec, &options).unwrap();
+        assert!(result.contains("total: z.coerce.bigint().optional(),"));
+    }
+}
+
+#[test]
+pub fn test_branded_types() {
+    let v3 = from_bytes(
+        br##"
+openapi: "3.0.0"
+info:
+  version: 1.0.0
+  title: Brand Example
+paths: {}
+components:
+  schemas:
+    UserId:
+      type: string
+      format: uuid
+      x-brand: UserId
+    Uuid:
+      type: object
+      properties:
+        value:
+          type: string
+    User:
+      type: object
+      required:
+        - id
+        - email
+      properties:
+        id:
+          $ref: "#/components/schemas/UserId"
+        email:
+          type: string
+          format: email
+        homepage:
+          type: string
+          format: uri
+          nullable: true
+        friend:
+          type: string
+          format: uuid
+        age:
+          type: integer
+          x-brand: Age
+        seen:
+          type: string
+          format: date-time
+"##,
+    )
+    .unwrap();
+    let v2 = from_bytes(
+        br##"
+swagger: "2.0"
+info:
+  version: 1.0.0
+  title: Brand Example
+paths: {}
+definitions:
+  UserId:
+    type: string
+    format: uuid
+    x-brand: UserId
+  Uuid:
+    type: object
+    properties:
+      value:
+        type: string
+  User:
+    type: object
+    required:
+      - id
+      - email
+    properties:
+      id:
+        $ref: "#/definitions/UserId"
+      email:
+        type: string
+        format: email
+      homepage:
+        type: string
+        format: uri
+        x-nullable: true
+      friend:
+        type: string
+        format: uuid
+      age:
+        type: integer
+        x-brand: Age
+      seen:
+        type: string
+        format: date-time
+"##,
+    )
+    .unwrap();
+    for spec in [&v3, &v2] {
+        let options = Options {
+            branded_types: true,
+            ..Options::default()
+        };
+        let output = generate(spec, &options).unwrap();
+        // The brands are declared once, before the definitions, which refer to them by name.
+        assert!(output.code.starts_with(
+            "// This file was generated using https://crates.io/crates/lupinas-lullaby\n\
+             export type Age = number & { readonly __brand: \"Age\" };\n\
+             export type Email = string & { readonly __brand: \"Email\" };\n\
+             export type Uri = string & { readonly __brand: \"Uri\" };\n\
+             export type Uuid = string & { readonly __brand: \"Uuid\" };\n"
+        ));
+        assert!(output.code.contains("  id: UserId;\n"));
+        assert!(output.code.contains("  email: Email;\n"));
+        assert!(output.code.contains("  homepage?: Uri | null;\n"));
+        assert!(output.code.contains("  friend?: Uuid;\n"));
+        assert!(output.code.contains("  age?: Age;\n"));
+        assert!(output.code.contains("  seen?: Date;\n"));
+        // A definition branded after its own name declares the brand, and names of brands are taken.
+        assert!(output
+            .code
+            .contains("export type UserId = string & { readonly __brand: \"UserId\" };"));
+        assert_eq!(output.code.matches("__brand: \"UserId\"").count(), 1);
+        assert_eq!(
+            output.renames,
+            vec![("Uuid".to_string(), "Uuid2".to_string())]
+        );
+
+        let options = Options {
+            target: Target::Zod,
+            branded_types: true,
+            ..Options::default()
+        };
+        let result = use_spec(spec, &options).unwrap();
+        assert!(result.contains("export type Email = string & z.BRAND<\"Email\">;\n"));
+        assert!(result.contains("export const UserId = z.string().brand<\"UserId\">();\n"));
+        assert!(result.contains("  email: z.string().brand<\"Email\">(),\n"));
+
+        // Brands are opt-in, and only the TypeScript targets have them.
+        let result = use_spec(spec, &Options::default()).unwrap();
+        assert!(!result.contains("__brand"));
+        assert!(result.contains("  email: string;\n"));
+        let options = Options {
+            target: Target::Rust,
+            branded_types: true,
+            ..Options::default()
+        };
+        let result = use_spec(spec, &options).unwrap();
+        assert!(result.contains("pub type UserId = String;"));
+        assert!(result.contains("pub struct Uuid {"));
+    }
+}
+
+#[test]
+pub fn test_webhooks() {
+    let spec = from_bytes(
+        br##"
+openapi: 3.1.0
+info:
+  version: 1.0.0
+  title: Webhook Example
+paths: {}
+webhooks:
+  petAdopted:
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              type: object
+              required:
+                - id
+              properties:
+                id:
+                  type: integer
+                adopter:
+                  type: string
+      responses:
+        "200":
+          description: Received
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  ok:
+                    type: boolean
+"##,
+    )
+    .unwrap();
+    // Inline schemas are named after the webhook, as they would be after an operation.
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result
+        .contains("export type PetAdoptedRequestBody = {\n  id: number;\n  adopter?: string;\n};"));
+    assert!(result.contains("export type PetAdoptedResponse200 = {\n  ok?: boolean;\n};"));
+
+    // The client only calls the operations of the API.
+    let options = Options {
+        client: true,
+        ..Options::default()
+    };
+    let result = use_spec(&spec, &options).unwrap();
+    assert!(result.contains("export type PetAdoptedRequestBody = {"));
+    assert!(!result.contains("petAdopted("));
+}
+
+#[test]
+pub fn test_unknown_version() {
+    let result = from_bytes(b"swagger: '1.2'\ndefinitions: {}\n");
+    assert!(matches!(result, Err(Error::UnknownVersion(Some(v))) if v == "1.2"));
+    let result = from_bytes(b"info: {}\n");
+    assert!(matches!(result, Err(Error::UnknownVersion(None))));
+}
+
+#[test]
+pub fn test_unresolved_reference() {
+    let result = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Pet:
+    type: object
+    properties:
+      owner:
+        $ref: "#/parameters/Owner"
+"##,
+    );
+    match result {
+        Err(Error::UnresolvedReference { pointer, reference }) => {
+            assert_eq!(pointer, "#/definitions/Pet/properties/owner");
+            assert_eq!(reference, "#/parameters/Owner");
+        }
+        result => panic!("expected an unresolved reference, got {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_unsupported_schema() {
+    let spec = from_bytes(
+        br##"
+openapi: "3.0.0"
+components:
+  schemas:
+    Pets:
+      type: array
+"##,
+    )
+    .unwrap();
+    match use_spec(&spec, &Options::default()) {
+        Err(Error::UnsupportedSchema { pointer, .. }) => {
+            assert_eq!(pointer, "#/components/schemas/Pets")
+        }
+        result => panic!("expected an unsupported schema, got {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_external_references() {
+    for path in &[
+        "./data/v2.0/json/petstore-separate/spec/swagger.json",
+        "./data/v2.0/yaml/petstore-separate/spec/swagger.yaml",
+    ] {
+        let result = use_spec(&from_path(path).unwrap(), &Options::default()).unwrap();
+        assert!(result.contains("export type Error = {"));
+        assert!(result.contains("export type Pet = {"));
+        assert!(result.contains("export type NewPet = Pet & {"));
+    }
+}
+
+#[test]
+pub fn test_nested_references() {
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Pet:
+    type: object
+    properties:
+      tags:
+        type: array
+        items:
+          type: string
+  Owner:
+    type: object
+    properties:
+      tags:
+        $ref: "#/definitions/Pet/properties/tags"
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("export type PetTags = string[];"));
+    assert!(result.contains("tags?: PetTags;"));
+}
+
+#[test]
+pub fn test_keyword_properties() {
+    // Properties named like the members of other objects are still schemas, whose references are kept.
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Link:
+    type: object
+    properties:
+      href:
+        type: string
+  Page:
+    type: object
+    properties:
+      links:
+        type: array
+        items:
+          $ref: "#/definitions/Link"
+      headers:
+        additionalProperties:
+          $ref: "#/definitions/Link"
+      responses:
+        $ref: "#/definitions/Link"
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result
+        .contains("  links?: Link[];\n  headers?: Record<string, Link>;\n  responses?: Link;\n"));
+
+    // Which includes references to the schema the property belongs to.
+    let spec = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Link:
+    type: object
+    properties:
+      href:
+        type: string
+      links:
+        type: array
+        items:
+          $ref: "#/definitions/Link"
+"##,
+    )
+    .unwrap();
+    let result = use_spec(&spec, &Options::default()).unwrap();
+    assert!(result.contains("export type Link = {\n  href?: string;\n  links?: Link[];\n};"));
+}
+
+#[test]
+pub fn test_cyclic_references() {
+    let result = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Cat:
+    $ref: "#/definitions/Dog"
+  Dog:
+    $ref: "#/definitions/Cat"
+"##,
+    );
+    assert!(matches!(result, Err(Error::CyclicReference { .. })));
+    let result = from_bytes(
+        br##"
+swagger: "2.0"
+definitions:
+  Pet:
+    $ref: "Pet.yaml"
+"##,
+    );
+    assert!(matches!(result, Err(Error::UnresolvedReference { .. })));
+}
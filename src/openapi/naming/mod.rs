@@ -0,0 +1,279 @@
+use indexmap::IndexMap;
+use std::collections::HashSet;
+
+/// Global types the generated code refers to, which definitions must not shadow.
+pub const RESERVED: &[&str] = &[
+    "Array", "Boolean", "Date", "Function", "Number", "Object", "Promise", "Record", "String",
+    "Symbol",
+];
+
+/// The types the generated client refers to besides [`RESERVED`].
+pub const CLIENT_RESERVED: &[&str] = &[
+    "Blob",
+    "BodyInit",
+    "ClientOptions",
+    "ClientRequest",
+    "FormData",
+    "Headers",
+    "Request",
+    "Response",
+    "URLSearchParams",
+];
+
+/// The types the generated Rust code refers to, which definitions must not shadow.
+pub const RUST_RESERVED: &[&str] = &[
+    "Box",
+    "Deserialize",
+    "HashMap",
+    "Option",
+    "Result",
+    "Self",
+    "Serialize",
+    "String",
+    "Vec",
+];
+
+/// Keywords which can't be used as Rust identifiers, even raw.
+const RUST_STRICT_KEYWORDS: &[&str] = &["crate", "self", "super"];
+
+/// Keywords which can only be used as raw Rust identifiers, e.g. `r#type`.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// The names the generated Python code refers to, which definitions must not shadow.
+pub const PYTHON_RESERVED: &[&str] = &[
+    "Any",
+    "BaseModel",
+    "ConfigDict",
+    "Dict",
+    "False",
+    "Field",
+    "List",
+    "Literal",
+    "None",
+    "NotRequired",
+    "Optional",
+    "True",
+    "Tuple",
+    "TypedDict",
+    "Union",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Turns arbitrary text into PascalCase, e.g. `in-progress` and `IN_PROGRESS` into `InProgress`.
+/// Words are separated by anything that isn't a letter or a digit.
+pub fn pascal_case(s: &str) -> String {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let word = if word.chars().all(|c| !c.is_ascii_lowercase()) {
+                word.to_ascii_lowercase()
+            } else {
+                word.to_string()
+            };
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Turns arbitrary text into snake_case, e.g. `petType` and `X-Request-ID` into `pet_type` and `x_request_id`.
+/// Words are separated by anything that isn't a letter or a digit, and by changes of case.
+pub fn snake_case(s: &str) -> String {
+    let mut words = vec![];
+    for word in s.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let chars = word.chars().collect::<Vec<_>>();
+        let mut start = 0;
+        for idx in 1..chars.len() {
+            let (previous, current) = (chars[idx - 1], chars[idx]);
+            // `petType` is split before `T`, `HTTPServer` before `S`.
+            let boundary = current.is_ascii_uppercase()
+                && (!previous.is_ascii_uppercase()
+                    || chars.get(idx + 1).is_some_and(char::is_ascii_lowercase));
+            if boundary {
+                words.push(chars[start..idx].iter().collect::<String>());
+                start = idx;
+            }
+        }
+        words.push(chars[start..].iter().collect::<String>());
+    }
+    words
+        .iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// A snake_case Rust identifier for arbitrary text, made raw if it is a keyword, e.g. `r#type`.
+pub fn rust_field_name(s: &str) -> String {
+    let name = snake_case(s);
+    if name.is_empty() {
+        "empty".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if RUST_STRICT_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else if RUST_KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Whether `s` can be used as is as a Python identifier.
+pub fn is_python_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !PYTHON_KEYWORDS.contains(&s)
+}
+
+/// A Python identifier for arbitrary text which doesn't start with an underscore,
+/// as pydantic ignores such attributes, e.g. `x_request_id` for `X-Request-ID` and `class_` for `class`.
+pub fn python_field_name(s: &str) -> String {
+    if is_python_identifier(s) && !s.starts_with('_') {
+        return s.to_string();
+    }
+    let name = snake_case(s);
+    if name.is_empty() {
+        "empty".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{}", name)
+    } else if PYTHON_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// An exported Go identifier for arbitrary text, e.g. `XRequestId` for `X-Request-ID`.
+pub fn go_name(s: &str) -> String {
+    let name = pascal_case(s);
+    if name.is_empty() {
+        "Empty".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("X{}", name)
+    } else {
+        name
+    }
+}
+
+/// Whether `s` can be used as is as a JavaScript identifier.
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// A PascalCase identifier for arbitrary text, which is never empty nor starts with a digit.
+pub fn identifier(s: &str) -> String {
+    let name = pascal_case(s);
+    if name.is_empty() {
+        "Empty".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// `base`, suffixed with 2, 3... until it isn't among the `taken` names.
+pub fn unique(base: &str, taken: &HashSet<String>) -> String {
+    (1..)
+        .map(|idx| {
+            if idx == 1 {
+                base.to_string()
+            } else {
+                format!("{}{}", base, idx)
+            }
+        })
+        .find(|name| !taken.contains(name))
+        .unwrap()
+}
+
+/// Suffixes the names that were already taken with 2, 3...
+pub fn deduplicate(names: Vec<String>) -> Vec<String> {
+    let mut taken = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let deduplicated = unique(&name, &taken);
+            taken.insert(deduplicated.clone());
+            deduplicated
+        })
+        .collect()
+}
+
+/// A PascalCase identifier for a definition key, e.g. `PetResponse` for `Pet-Response`.
+/// Keys which are already identifiers only get their first letter capitalized.
+pub fn type_name(key: &str) -> String {
+    if is_identifier(key) {
+        let mut chars = key.chars();
+        chars
+            .next()
+            .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+            .unwrap_or_default()
+    } else {
+        identifier(key)
+    }
+}
+
+/// The type name of every definition key, without collisions between them nor with [`RESERVED`] and `reserved`.
+/// Keys which are already valid type names keep them, the others get suffixed on collision.
+pub fn type_names<'a, I>(keys: I, reserved: &[&str]) -> IndexMap<String, String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let keys = keys.into_iter().collect::<Vec<_>>();
+    let mut taken = RESERVED
+        .iter()
+        .chain(reserved)
+        .map(|s| s.to_string())
+        .collect::<HashSet<_>>();
+    let mut names = IndexMap::new();
+    for key in keys.iter() {
+        if type_name(key) == *key && taken.insert(key.to_string()) {
+            names.insert(key.to_string(), key.to_string());
+        }
+    }
+    for key in keys.iter() {
+        if !names.contains_key(*key) {
+            let base = type_name(key);
+            let name = unique(&base, &taken);
+            taken.insert(name.clone());
+            names.insert(key.to_string(), name);
+        }
+    }
+    keys.iter()
+        .map(|key| (key.to_string(), names[*key].clone()))
+        .collect()
+}
+
+/// The prefix of the type names of an operation, e.g. `ListPets` for the `operationId` `listPets`.
+/// Operations without an `operationId` are named after their method and path,
+/// e.g. `GetPetsPetId` for `GET /pets/{petId}`.
+pub fn operation_name(operation_id: Option<&str>, method: &str, path: &str) -> String {
+    match operation_id {
+        Some(operation_id) if !pascal_case(operation_id).is_empty() => identifier(operation_id),
+        _ => identifier(&format!("{} {}", method, path)),
+    }
+}
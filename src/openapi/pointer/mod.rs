@@ -0,0 +1,19 @@
+/// Appends `segment` to a [JSON pointer](https://tools.ietf.org/html/rfc6901),
+/// escaping `~` and `/` as required by the RFC.
+pub fn join<T: AsRef<str>>(pointer: &str, segment: T) -> String {
+    format!(
+        "{}/{}",
+        pointer,
+        segment.as_ref().replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// Splits a JSON pointer (without the leading `#`) into its unescaped segments.
+pub fn split(pointer: &str) -> Vec<String> {
+    pointer.split('/').skip(1).map(unescape).collect()
+}
+
+/// Reverts the escaping done by [`join`] on a single segment.
+pub fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
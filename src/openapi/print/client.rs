@@ -0,0 +1,447 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use super::{Printer, Quote};
+use crate::ir::{Operation, ParameterLocation};
+use crate::naming;
+use crate::repr::{
+    AdditionalProperties, Definition, Documentation, JavaScriptType, JavaScriptValue, Property,
+};
+
+/// The code shared by the operations of the client, written with the default format.
+const RUNTIME: &str = r#"export type ClientOptions = {
+  /**
+   * Prepended to the path of every request, e.g. `https://petstore.swagger.io/v1`.
+   */
+  baseUrl?: string;
+  /**
+   * Called with every request before it is sent, e.g. to add credentials.
+   */
+  intercept?: (request: Request) => Request | Promise<Request>;
+  /**
+   * Sends the requests, defaults to the global `fetch`.
+   */
+  fetch?: (request: Request) => Promise<Response>;
+};
+
+type ClientRequest = {
+  path?: object;
+  query?: object;
+  headers?: object;
+  body?: unknown;
+};
+
+const delimiters: Record<string, string> = {
+  form: ",",
+  spaceDelimited: " ",
+  pipeDelimited: "|",
+  tabDelimited: "\t",
+};
+
+function isJson(mediaType: string): boolean {
+  const essence = mediaType.split(";")[0].trim().toLowerCase();
+  return essence === "application/json" || essence.endsWith("+json");
+}
+
+function serializeValue(value: unknown): string {
+  if (value instanceof Date) {
+    return value.toISOString();
+  } else if (Array.isArray(value)) {
+    return value.map(serializeValue).join(",");
+  } else if (value !== null && typeof value === "object") {
+    return Object.entries(value).flat().map(serializeValue).join(",");
+  }
+  return String(value);
+}
+
+function serializePath(value: unknown): string {
+  return encodeURIComponent(serializeValue(value));
+}
+
+function serializeQuery(
+  query: object,
+  styles: Record<string, [string, boolean]>,
+): URLSearchParams {
+  const search = new URLSearchParams();
+  for (const [name, value] of Object.entries(query)) {
+    const [style, explode] = styles[name] ?? ["form", true];
+    if (value === undefined || value === null) {
+      continue;
+    } else if (Array.isArray(value) && explode) {
+      value.forEach((v) => search.append(name, serializeValue(v)));
+    } else if (Array.isArray(value)) {
+      const delimiter = delimiters[style] ?? ",";
+      search.append(name, value.map(serializeValue).join(delimiter));
+    } else if (typeof value === "object" && !(value instanceof Date)) {
+      for (const [key, v] of Object.entries(value)) {
+        if (style === "deepObject") {
+          search.append(`${name}[${key}]`, serializeValue(v));
+        } else if (explode) {
+          search.append(key, serializeValue(v));
+        }
+      }
+      if (style !== "deepObject" && !explode) {
+        search.append(name, serializeValue(value));
+      }
+    } else {
+      search.append(name, serializeValue(value));
+    }
+  }
+  return search;
+}
+
+function serializeBody(mediaType: string, body: unknown): BodyInit {
+  if (mediaType === "multipart/form-data") {
+    const form = new FormData();
+    for (const [name, value] of Object.entries(body as object)) {
+      if (value instanceof Blob) {
+        form.append(name, value);
+      } else if (value !== undefined) {
+        form.append(name, serializeValue(value));
+      }
+    }
+    return form;
+  } else if (mediaType === "application/x-www-form-urlencoded") {
+    return serializeQuery(body as object, {});
+  } else if (isJson(mediaType)) {
+    return JSON.stringify(body);
+  }
+  return body as BodyInit;
+}
+
+async function send(
+  options: ClientOptions,
+  method: string,
+  path: string,
+  request: ClientRequest,
+  styles: Record<string, [string, boolean]> = {},
+  mediaType?: string,
+): Promise<any> {
+  const search = serializeQuery(request.query ?? {}, styles).toString();
+  const headers = new Headers();
+  for (const [name, value] of Object.entries(request.headers ?? {})) {
+    if (value !== undefined && value !== null) {
+      headers.set(name, serializeValue(value));
+    }
+  }
+  let body: BodyInit | undefined = undefined;
+  if (mediaType !== undefined && request.body !== undefined) {
+    body = serializeBody(mediaType, request.body);
+    // The boundary of multipart bodies is set by `fetch`.
+    if (mediaType !== "multipart/form-data") {
+      headers.set("Content-Type", mediaType);
+    }
+  }
+  const url = `${options.baseUrl ?? ""}${path}${search ? `?${search}` : ""}`;
+  let fetchRequest = new Request(url, { method, headers, body });
+  if (options.intercept) {
+    fetchRequest = await options.intercept(fetchRequest);
+  }
+  const response = await (options.fetch ?? fetch)(fetchRequest);
+  const text = await response.text();
+  const json = isJson(response.headers.get("Content-Type") ?? "");
+  const data = text && json ? JSON.parse(text) : text || undefined;
+  return { status: response.status, data, response };
+}
+"#;
+
+/// Escapes `s` for a template literal.
+fn escape_template(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+impl<'a> Printer<'a> {
+    /// The `createClient` function with one method per operation, preceded by the types of their requests and results.
+    /// `types` are the names of the generated types, references to the others are replaced with `unknown`.
+    /// Webhooks are left out, since the API calls them.
+    pub fn print_client(&self, operations: &[Operation], types: &HashSet<String>) -> String {
+        let operations = operations
+            .iter()
+            .filter(|operation| !operation.webhook)
+            .collect::<Vec<_>>();
+        let definitions = operations
+            .iter()
+            .flat_map(|operation| {
+                request_definition(operation, types)
+                    .into_iter()
+                    .chain(Some(result_definition(operation, types)))
+            })
+            .map(|definition| self.print_definition(&definition))
+            .collect::<Vec<_>>();
+        format!(
+            "{}\n{}\n\nexport function createClient(options: ClientOptions = {{}}) {{\n{}return {{\n{}{}}}{}\n}}",
+            self.print_runtime(),
+            definitions.join("\n"),
+            self.indent(1),
+            operations
+                .iter()
+                .map(|operation| self.print_method(operation, types))
+                .collect::<String>(),
+            self.indent(1),
+            self.terminator()
+        )
+    }
+
+    /// [`RUNTIME`] in the format of the printer.
+    fn print_runtime(&self) -> String {
+        RUNTIME
+            .lines()
+            .map(|line| {
+                let content = line.trim_start_matches(' ');
+                let level = (line.len() - content.len()) / 2;
+                let content = match self.format.quote {
+                    Quote::Single => content.replace('"', "'"),
+                    Quote::Double => content.to_string(),
+                };
+                let content = match content.strip_suffix(';') {
+                    Some(statement) if !self.format.semicolons => statement.to_string(),
+                    _ => content,
+                };
+                format!("{}{}\n", self.indent(level), content)
+            })
+            .collect()
+    }
+
+    fn print_method(&self, operation: &Operation, types: &HashSet<String>) -> String {
+        let level = 2;
+        let request = request_definition(operation, types);
+        let parameter = match request.as_ref() {
+            Some(request) => match &request.jtype {
+                JavaScriptType::AnonymousObject(rows, _) if rows.values().all(|p| !p.required) => {
+                    format!("request: {} = {{}}", request.name)
+                }
+                _ => format!("request: {}", request.name),
+            },
+            None => String::new(),
+        };
+        let signature = format!(
+            "async {}({}): Promise<{}> {{",
+            self.print_key(&method_name(&operation.name)),
+            parameter,
+            operation.type_name("Result")
+        );
+        let signature = if self.indent(level).chars().count() + signature.chars().count()
+            > self.format.line_width
+            && !parameter.is_empty()
+        {
+            format!(
+                "async {}(\n{}{},\n{}): Promise<{}> {{",
+                self.print_key(&method_name(&operation.name)),
+                self.indent(level + 1),
+                parameter,
+                self.indent(level),
+                operation.type_name("Result")
+            )
+        } else {
+            signature
+        };
+        let styles = operation
+            .parameters
+            .iter()
+            .filter(|p| p.location == ParameterLocation::Query && (p.style != "form" || !p.explode))
+            .map(|p| {
+                format!(
+                    "{}: [{}, {}]",
+                    self.print_key(&p.name),
+                    self.print_string(&p.style),
+                    p.explode
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut arguments = vec![
+            "options".to_string(),
+            self.print_string(&operation.method.to_ascii_uppercase()),
+            self.print_path(operation),
+            if request.is_some() {
+                "request".to_string()
+            } else {
+                "{}".to_string()
+            },
+        ];
+        if let Some(body) = operation.request_body.as_ref() {
+            arguments.push(if styles.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", styles.join(", "))
+            });
+            arguments.push(self.print_string(&body.media_type));
+        } else if !styles.is_empty() {
+            arguments.push(format!("{{ {} }}", styles.join(", ")));
+        }
+        format!(
+            "{}{}{}\n{}return {}{}\n{}}},\n",
+            self.print_docs(&operation.docs, level),
+            self.indent(level),
+            signature,
+            self.indent(level + 1),
+            self.print_call("send", &arguments, level + 1),
+            self.terminator(),
+            self.indent(level)
+        )
+    }
+
+    /// A returned call on a single line if it fits, with one argument per line otherwise.
+    fn print_call(&self, function: &str, arguments: &[String], level: usize) -> String {
+        let inline = format!("{}({})", function, arguments.join(", "));
+        let column = self.indent(level).chars().count()
+            + "return ".len()
+            + inline.chars().count()
+            + self.terminator().len();
+        if column <= self.format.line_width {
+            inline
+        } else {
+            format!(
+                "{}(\n{}{})",
+                function,
+                arguments
+                    .iter()
+                    .map(|a| format!("{}{},\n", self.indent(level + 1), a))
+                    .collect::<String>(),
+                self.indent(level)
+            )
+        }
+    }
+
+    /// The path of the operation as a template literal interpolating its path parameters.
+    fn print_path(&self, operation: &Operation) -> String {
+        let mut path = String::new();
+        let mut rest = operation.path.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            let name = &rest[start + 1..end];
+            path.push_str(&escape_template(&rest[..start]));
+            if operation
+                .parameters
+                .iter()
+                .any(|p| p.location == ParameterLocation::Path && p.name == name)
+            {
+                let access = if naming::is_identifier(name) {
+                    format!(".{}", name)
+                } else {
+                    format!("[{}]", self.print_string(name))
+                };
+                path.push_str(&format!("${{serializePath(request.path{})}}", access));
+            } else {
+                path.push_str(&escape_template(&rest[start..=end]));
+            }
+            rest = &rest[end + 1..];
+        }
+        path.push_str(&escape_template(rest));
+        format!("`{}`", path)
+    }
+}
+
+/// The name of the method of an operation, e.g. `listPets`.
+fn method_name(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// `name` if it is one of the generated types, `unknown` otherwise.
+fn reference(name: String, types: &HashSet<String>) -> JavaScriptType {
+    if types.contains(&name) {
+        JavaScriptType::Typename(name)
+    } else {
+        JavaScriptType::typename("unknown")
+    }
+}
+
+/// The type of the argument of the method of an operation, if it takes any.
+fn request_definition(operation: &Operation, types: &HashSet<String>) -> Option<Definition> {
+    let mut rows = IndexMap::new();
+    for (location, suffix) in Operation::PARAMETERS {
+        let parameters = operation
+            .parameters
+            .iter()
+            .filter(|p| p.location == *location)
+            .collect::<Vec<_>>();
+        if !parameters.is_empty() {
+            let key = match location {
+                ParameterLocation::Path => "path",
+                ParameterLocation::Query => "query",
+                _ => "headers",
+            };
+            rows.insert(
+                key.to_string(),
+                Property {
+                    required: parameters.iter().any(|p| p.field.required),
+                    jtype: reference(operation.type_name(suffix), types),
+                    docs: Documentation::default(),
+                },
+            );
+        }
+    }
+    if let Some(body) = operation.request_body.as_ref() {
+        rows.insert(
+            "body".to_string(),
+            Property {
+                required: body.field.required,
+                jtype: reference(operation.type_name("RequestBody"), types),
+                docs: Documentation::default(),
+            },
+        );
+    }
+    (!rows.is_empty()).then(|| Definition {
+        name: operation.type_name("Request"),
+        jtype: JavaScriptType::AnonymousObject(rows, AdditionalProperties::Forbidden),
+        docs: Documentation::default(),
+    })
+}
+
+/// The type of the result of the method of an operation, one variant per response.
+fn result_definition(operation: &Operation, types: &HashSet<String>) -> Definition {
+    let variant = |status: JavaScriptType, data: JavaScriptType| {
+        let rows = vec![
+            ("status", status),
+            ("data", data),
+            ("response", JavaScriptType::typename("Response")),
+        ]
+        .into_iter()
+        .map(|(key, jtype)| {
+            let property = Property {
+                required: true,
+                jtype,
+                docs: Documentation::default(),
+            };
+            (key.to_string(), property)
+        })
+        .collect();
+        JavaScriptType::AnonymousObject(rows, AdditionalProperties::Forbidden)
+    };
+    let variants = operation
+        .responses
+        .iter()
+        .map(|response| {
+            let status = match response.status.parse::<f64>() {
+                Ok(status) => JavaScriptType::Value(Box::new(JavaScriptValue::Number(status))),
+                Err(_) => JavaScriptType::typename("number"),
+            };
+            let data = match response.schema {
+                Some(_) => reference(operation.response_type_name(&response.status), types),
+                None => JavaScriptType::typename("undefined"),
+            };
+            variant(status, data)
+        })
+        .collect::<Vec<_>>();
+    Definition {
+        name: operation.type_name("Result"),
+        jtype: if variants.is_empty() {
+            variant(
+                JavaScriptType::typename("number"),
+                JavaScriptType::typename("unknown"),
+            )
+        } else {
+            JavaScriptType::sum(variants)
+        },
+        docs: Documentation::default(),
+    }
+}
@@ -0,0 +1,465 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+
+use super::{enum_member_names, escape};
+use crate::ir::{self, dependency_order, is_recursive, Additional, Definition, Kind, Type};
+use crate::naming;
+use crate::repr::{Documentation, JavaScriptValue};
+
+/// What the Go type of a definition may refer to.
+struct Scope<'a> {
+    definitions: &'a HashMap<&'a str, &'a Definition>,
+    /// The definitions of the same cycle of references as the one being declared.
+    cycle: &'a HashSet<&'a str>,
+    /// Whether `json.RawMessage` is referred to, which needs `encoding/json`.
+    json: &'a Cell<bool>,
+}
+
+/// A line of a struct.
+enum Field {
+    Embedded(String),
+    Named {
+        docs: String,
+        name: String,
+        gtype: String,
+        tag: String,
+    },
+}
+
+/// A Go file of the package `package` declaring every definition.
+/// Objects are declared as structs, string enums as string types with a constant for each value,
+/// and the other definitions as aliases, or as types of their own if recursive, which aliases can't be.
+/// Optional and nullable properties are pointers unless their type can be `nil` already,
+/// and unions are `json.RawMessage` as Go can't express them.
+pub fn print_go(definitions: &[Definition], package: &str) -> String {
+    let by_name = definitions
+        .iter()
+        .map(|d| (d.name.as_str(), d))
+        .collect::<HashMap<_, _>>();
+    let mut cycles = HashMap::new();
+    for group in dependency_order(definitions) {
+        if is_recursive(&group) {
+            let names = group
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<HashSet<_>>();
+            for definition in group {
+                cycles.insert(definition.name.as_str(), names.clone());
+            }
+        }
+    }
+    let json = Cell::new(false);
+    let acyclic = HashSet::new();
+    // The constants of the enums are in the same namespace as the types.
+    let mut taken = definitions
+        .iter()
+        .map(|d| d.name.clone())
+        .collect::<HashSet<_>>();
+    let items = definitions
+        .iter()
+        .map(|definition| {
+            let scope = Scope {
+                definitions: &by_name,
+                cycle: cycles.get(definition.name.as_str()).unwrap_or(&acyclic),
+                json: &json,
+            };
+            print_definition(definition, &scope, &mut taken)
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "\npackage {}\n\n{}{}",
+        package,
+        if json.get() {
+            "import \"encoding/json\"\n\n"
+        } else {
+            ""
+        },
+        items.join("\n\n")
+    )
+}
+
+fn print_definition(definition: &Definition, scope: &Scope, taken: &mut HashSet<String>) -> String {
+    let name = &definition.name;
+    let docs = print_docs(&definition.schema.meta.docs, 0);
+    let ty = declared_type(&definition.schema, scope);
+    if let Some(values) = ty.string_enum_values() {
+        let constants = enum_member_names(&definition.schema.meta.docs, &values)
+            .iter()
+            .map(|member| {
+                let base = format!("{}{}", name, naming::go_name(member));
+                let constant = naming::unique(&base, taken);
+                taken.insert(constant.clone());
+                constant
+            })
+            .collect::<Vec<_>>();
+        let width = constants
+            .iter()
+            .map(|c| c.chars().count())
+            .max()
+            .unwrap_or(0);
+        return format!(
+            "{}type {} string\n\nconst (\n{})",
+            docs,
+            name,
+            constants
+                .iter()
+                .zip(values)
+                .map(|(constant, value)| format!(
+                    "\t{:width$} {} = {}\n",
+                    constant,
+                    name,
+                    print_string(value),
+                    width = width
+                ))
+                .collect::<String>()
+        );
+    }
+    if is_struct(&ty, scope) {
+        let fields = struct_fields(&ty, scope);
+        return if fields.is_empty() {
+            format!("{}type {} struct{{}}", docs, name)
+        } else {
+            format!(
+                "{}type {} struct {{\n{}}}",
+                docs,
+                name,
+                print_fields(&fields)
+            )
+        };
+    }
+    let gtype = go_type(&ty, scope);
+    // An alias can't refer to itself, but a type of its own loses the methods of `json.RawMessage`.
+    let recursive = gtype
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|t| scope.cycle.contains(t));
+    format!(
+        "{}type {}{} {}",
+        docs,
+        name,
+        if recursive { "" } else { " =" },
+        gtype
+    )
+}
+
+/// gofmt aligns the names, types and tags of consecutive fields, which comments and embedded fields interrupt.
+fn print_fields(fields: &[Field]) -> String {
+    let mut code = String::new();
+    let mut run = vec![];
+    let flush = |run: &mut Vec<(&String, &String, &String)>, code: &mut String| {
+        let names = run
+            .iter()
+            .map(|(n, _, _)| n.chars().count())
+            .max()
+            .unwrap_or(0);
+        let types = run
+            .iter()
+            .map(|(_, t, _)| t.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (name, gtype, tag) in run.drain(..) {
+            code.push_str(&format!(
+                "\t{:names$} {:types$} {}\n",
+                name,
+                gtype,
+                tag,
+                names = names,
+                types = types
+            ));
+        }
+    };
+    for field in fields {
+        match field {
+            Field::Embedded(name) => {
+                flush(&mut run, &mut code);
+                code.push_str(&format!("\t{}\n", name));
+            }
+            Field::Named {
+                docs,
+                name,
+                gtype,
+                tag,
+            } => {
+                if !docs.is_empty() {
+                    flush(&mut run, &mut code);
+                    code.push_str(docs);
+                }
+                run.push((name, gtype, tag));
+            }
+        }
+    }
+    flush(&mut run, &mut code);
+    code
+}
+
+/// The fields of a struct declaring an object or an intersection of objects.
+/// Referenced structs are embedded in it, and the properties already in them are left out.
+fn struct_fields(ty: &Type, scope: &Scope) -> Vec<Field> {
+    let parts = ty.intersected();
+    let embedded = parts
+        .iter()
+        .filter(|part| !part.meta.nullable && matches!(part.kind, Kind::Reference(_)))
+        .flat_map(|part| object_keys(part, scope, &mut HashSet::new()))
+        .collect::<HashSet<_>>();
+    let mut fields = vec![];
+    for part in parts.iter() {
+        match &part.kind {
+            _ if part.meta.nullable => (),
+            Kind::Reference(name) => fields.push(Field::Embedded(name.clone())),
+            Kind::Object(properties, _) => fields.extend(
+                properties
+                    .iter()
+                    .filter(|(k, _)| !embedded.contains(k.as_str()))
+                    .map(|(k, f)| property_field(k, f, scope)),
+            ),
+            _ => (),
+        }
+    }
+    let names = naming::deduplicate(
+        fields
+            .iter()
+            .map(|field| match field {
+                Field::Embedded(name) | Field::Named { name, .. } => name.clone(),
+            })
+            .collect(),
+    );
+    for (field, unique) in fields.iter_mut().zip(names) {
+        if let Field::Named { name, .. } = field {
+            *name = unique;
+        }
+    }
+    fields
+}
+
+/// A field for a property, omitted when empty if it isn't required.
+fn property_field(key: &str, field: &ir::Field, scope: &Scope) -> Field {
+    let schema = &field.schema;
+    let nullable = schema.meta.nullable;
+    let ty = schema.non_null();
+    let gtype = go_type(&ty, scope);
+    // A struct can't contain itself, only a pointer to itself.
+    let recursive = matches!(&ty.kind, Kind::Reference(t) if scope.cycle.contains(t.as_str()));
+    let pointer = (!field.required || nullable || recursive)
+        && !is_nilable(&gtype, scope, &mut HashSet::new());
+    let tag = format!(
+        "json:\"{}{}\"",
+        escape(key, '"'),
+        if field.required { "" } else { ",omitempty" }
+    );
+    Field::Named {
+        docs: print_docs(&schema.meta.docs, 1),
+        name: naming::go_name(key),
+        gtype: if pointer {
+            format!("*{}", gtype)
+        } else {
+            gtype
+        },
+        tag: if tag.contains('`') {
+            print_string(&tag)
+        } else {
+            format!("`{}`", tag)
+        },
+    }
+}
+
+/// The type of a field, an element or an alias, sized after the `format` of the schema.
+fn go_type(ty: &Type, scope: &Scope) -> String {
+    if ty.meta.nullable {
+        let gtype = go_type(&ty.non_null(), scope);
+        return if is_nilable(&gtype, scope, &mut HashSet::new()) {
+            gtype
+        } else {
+            format!("*{}", gtype)
+        };
+    }
+    match &ty.kind {
+        Kind::String | Kind::Date => "string".to_string(),
+        Kind::Integer => match ty.meta.docs.format.as_deref() {
+            Some("int32") => "int32",
+            _ => "int64",
+        }
+        .to_string(),
+        Kind::Number => match ty.meta.docs.format.as_deref() {
+            Some("int32") => "int32",
+            Some("int64") => "int64",
+            Some("float") => "float32",
+            _ => "float64",
+        }
+        .to_string(),
+        Kind::Boolean => "bool".to_string(),
+        Kind::Reference(name) | Kind::Mapped(name) | Kind::Other(name) => match name.as_str() {
+            "bigint" => "int64".to_string(),
+            name if scope.definitions.contains_key(name) => name.to_string(),
+            _ => "interface{}".to_string(),
+        },
+        Kind::Branded(_, t) => go_type(t, scope),
+        Kind::Array(t) => format!("[]{}", go_type(t, scope)),
+        Kind::Tuple(..) => "[]interface{}".to_string(),
+        Kind::Object(fields, additional) if fields.is_empty() => format!(
+            "map[string]{}",
+            match additional {
+                Additional::Allowed(t) => go_type(t, scope),
+                _ => "interface{}".to_string(),
+            }
+        ),
+        Kind::Const(value) => value_type(value).to_string(),
+        // A union of literals of the same type is that type, e.g. `1 | 2`.
+        Kind::Enum(_) | Kind::Union(_) => {
+            let types = match &ty.kind {
+                Kind::Enum(values) => values.iter().map(value_type).collect(),
+                Kind::Union(variants) => variants
+                    .iter()
+                    .map(|v| match &v.kind {
+                        Kind::Const(value) if !v.meta.nullable => Some(value_type(value)),
+                        _ => None,
+                    })
+                    .collect::<Option<HashSet<_>>>()
+                    .unwrap_or_default(),
+                _ => HashSet::new(),
+            };
+            match types.into_iter().collect::<Vec<_>>().as_slice() {
+                [gtype] => gtype.to_string(),
+                _ => {
+                    scope.json.set(true);
+                    "json.RawMessage".to_string()
+                }
+            }
+        }
+        // The variants of a tagged union are the types they narrow.
+        Kind::Intersection(_) | Kind::Tagged { .. } => {
+            let parts = ty.intersected();
+            let types = parts
+                .iter()
+                .filter(|part| part.meta.nullable || !matches!(part.kind, Kind::Object(..)))
+                .collect::<Vec<_>>();
+            match types.as_slice() {
+                [t] => go_type(t, scope),
+                _ => {
+                    scope.json.set(true);
+                    "json.RawMessage".to_string()
+                }
+            }
+        }
+        _ => "interface{}".to_string(),
+    }
+}
+
+/// The Go type of a literal.
+fn value_type(value: &JavaScriptValue) -> &'static str {
+    match value {
+        JavaScriptValue::String(_) => "string",
+        JavaScriptValue::Number(_) => "float64",
+        JavaScriptValue::Boolean(_) => "bool",
+        _ => "interface{}",
+    }
+}
+
+/// Whether the Go type can be `nil` already, so that it doesn't need to be a pointer to be optional.
+fn is_nilable<'a>(gtype: &str, scope: &Scope<'a>, visited: &mut HashSet<&'a str>) -> bool {
+    gtype.starts_with('*')
+        || gtype.starts_with("[]")
+        || gtype.starts_with("map[")
+        || gtype == "interface{}"
+        || gtype == "json.RawMessage"
+        || match scope.definitions.get(gtype) {
+            Some(definition) if visited.insert(definition.name.as_str()) => {
+                let ty = declared_type(&definition.schema, scope);
+                !is_struct(&ty, scope)
+                    && ty.string_enum_values().is_none()
+                    && is_nilable(&go_type(&ty, scope), scope, visited)
+            }
+            _ => false,
+        }
+}
+
+/// Go can't declare a nullable struct, which is declared as if it wasn't.
+fn declared_type<'a>(ty: &'a Type, scope: &Scope) -> Cow<'a, Type> {
+    let non_null = ty.non_null();
+    if ty.meta.nullable && is_struct(&non_null, scope) {
+        Cow::Owned(non_null)
+    } else {
+        Cow::Borrowed(ty)
+    }
+}
+
+/// Whether `ty` is declared as a struct, i.e. it is an object which isn't a map,
+/// or an intersection of objects and structs.
+fn is_struct(ty: &Type, scope: &Scope) -> bool {
+    match &ty.kind {
+        _ if ty.meta.nullable => false,
+        Kind::Object(fields, additional) => {
+            !fields.is_empty() || !matches!(additional, Additional::Allowed(_))
+        }
+        Kind::Intersection(_) | Kind::Tagged { .. } => {
+            ty.intersected().iter().all(|part| match &part.kind {
+                _ if part.meta.nullable => false,
+                Kind::Object(..) => true,
+                Kind::Reference(name) => scope.definitions.get(name.as_str()).is_some_and(|d| {
+                    !d.schema.meta.nullable && matches!(d.schema.kind, Kind::Object(..))
+                }),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// The properties of an object, a reference to one or an intersection of them.
+fn object_keys<'a>(
+    ty: &'a Type,
+    scope: &Scope<'a>,
+    visited: &mut HashSet<&'a str>,
+) -> HashSet<&'a str> {
+    match &ty.kind {
+        _ if ty.meta.nullable => HashSet::new(),
+        Kind::Object(fields, _) => fields.keys().map(String::as_str).collect(),
+        Kind::Reference(name) if visited.insert(name) => scope
+            .definitions
+            .get(name.as_str())
+            .map(|d| object_keys(&d.schema, scope, visited))
+            .unwrap_or_default(),
+        Kind::Intersection(parts) => parts
+            .iter()
+            .flat_map(|p| object_keys(p, scope, visited))
+            .collect(),
+        Kind::Tagged {
+            variant, property, ..
+        } => {
+            let mut keys = object_keys(variant, scope, visited);
+            keys.insert(property.as_str());
+            keys
+        }
+        _ => HashSet::new(),
+    }
+}
+
+/// A string literal, escaped so that it evaluates back to `s`.
+fn print_string(s: &str) -> String {
+    format!("\"{}\"", escape(s, '"'))
+}
+
+/// The comment of a type or a field, empty if there is nothing to document.
+fn print_docs(docs: &Documentation, level: usize) -> String {
+    let text = |t: &String| {
+        t.trim_end()
+            .lines()
+            .map(|l| l.trim_end().to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut lines = docs.title.iter().flat_map(text).collect::<Vec<_>>();
+    if docs.title.is_some() && docs.description.is_some() {
+        lines.push(String::new());
+    }
+    lines.extend(docs.description.iter().flat_map(text));
+    let indent = "\t".repeat(level);
+    lines
+        .iter()
+        .map(|l| {
+            if l.is_empty() {
+                format!("{}//\n", indent)
+            } else {
+                format!("{}// {}\n", indent, l)
+            }
+        })
+        .collect()
+}
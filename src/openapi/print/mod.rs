@@ -0,0 +1,601 @@
+mod client;
+mod go;
+mod python;
+mod rust;
+mod zod;
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+pub use self::go::print_go;
+pub use self::python::{print_python, Classes};
+pub use self::rust::print_rust;
+use crate::naming;
+use crate::repr::{
+    brands, AdditionalProperties, Definition, Documentation, JavaScriptType, JavaScriptValue,
+    Property,
+};
+
+/// The quotes around string literals and property names.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Quote {
+    Single,
+    Double,
+}
+
+/// How string enums are declared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Enums {
+    /// `export type X = "a" | "b";`
+    Union,
+    /// `export enum X { A = "a", B = "b" }`
+    Enum,
+    /// `export const X = ["a", "b"] as const;` and a type of the same name for its elements.
+    Const,
+}
+
+/// Controls how the TypeScript code is laid out.
+/// The default follows the defaults of `prettier`.
+#[derive(Clone, Debug)]
+pub struct Format {
+    /// The indentation of one level, e.g. two spaces or a tab.
+    pub indent: String,
+    pub quote: Quote,
+    /// Terminate type aliases and properties with `;`.
+    pub semicolons: bool,
+    /// Unions that don't fit in this many columns are split, one variant per line.
+    pub line_width: usize,
+    /// Declare objects as interfaces, extending the references they are intersected with.
+    /// Other types are still declared as type aliases.
+    pub interfaces: bool,
+    /// Only applies to definitions which are string enums.
+    pub enums: Enums,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            indent: "  ".to_string(),
+            quote: Quote::Double,
+            semicolons: true,
+            line_width: 80,
+            interfaces: false,
+            enums: Enums::Union,
+        }
+    }
+}
+
+pub struct Printer<'a> {
+    format: &'a Format,
+    /// The definitions an interface can extend.
+    objects: HashSet<String>,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(format: &'a Format) -> Printer<'a> {
+        Printer {
+            format,
+            objects: HashSet::new(),
+        }
+    }
+
+    /// A printer for the given definitions, which is needed to know which of them interfaces can extend.
+    pub fn with_definitions(format: &'a Format, definitions: &[Definition]) -> Printer<'a> {
+        let mut objects = HashSet::new();
+        // A definition referring to an object type is itself one, so repeat until nothing changes.
+        loop {
+            let len = objects.len();
+            for definition in definitions {
+                if is_object_type(&definition.jtype, &objects) {
+                    objects.insert(definition.name.clone());
+                }
+            }
+            if objects.len() == len {
+                break;
+            }
+        }
+        Printer { format, objects }
+    }
+
+    fn indent(&self, level: usize) -> String {
+        self.format.indent.repeat(level)
+    }
+
+    fn terminator(&self) -> &'static str {
+        if self.format.semicolons {
+            ";"
+        } else {
+            ""
+        }
+    }
+
+    pub fn print_definition(&self, definition: &Definition) -> String {
+        // A definition branded after its own name declares the brand.
+        if let JavaScriptType::Branded(brand, jtype) = &definition.jtype {
+            if *brand == definition.name {
+                return format!(
+                    "{}export type {} = {}{}",
+                    self.print_docs(&definition.docs, 0),
+                    definition.name,
+                    self.print_brand(brand, jtype),
+                    self.terminator()
+                );
+            }
+        }
+        if let Some(values) = definition.jtype.string_enum_values() {
+            match self.format.enums {
+                Enums::Union => (),
+                Enums::Enum => return self.print_enum(definition, &values),
+                Enums::Const => {
+                    return format!(
+                    "{}export const {} = [{}] as const{}\nexport type {} = (typeof {})[number]{}",
+                    self.print_docs(&definition.docs, 0),
+                    definition.name,
+                    values
+                        .iter()
+                        .map(|v| self.print_string(v))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    self.terminator(),
+                    definition.name,
+                    definition.name,
+                    self.terminator()
+                )
+                }
+            }
+        }
+        if self.format.interfaces {
+            if let Some((extends, rows, additional)) = self.interface_members(&definition.jtype) {
+                return format!(
+                    "{}export interface {}{} {}",
+                    self.print_docs(&definition.docs, 0),
+                    definition.name,
+                    if extends.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" extends {}", extends.join(", "))
+                    },
+                    self.print_object(&rows, &additional, 0)
+                );
+            }
+        }
+        format!(
+            "{}{}",
+            self.print_docs(&definition.docs, 0),
+            self.print_member(
+                format!("export type {} =", definition.name),
+                &definition.jtype,
+                0
+            )
+        )
+    }
+
+    /// An `enum` with a member for each of the `values` of the definition, named by [`enum_member_names`].
+    fn print_enum(&self, definition: &Definition, values: &[&str]) -> String {
+        format!(
+            "{}export enum {} {{\n{}}}",
+            self.print_docs(&definition.docs, 0),
+            definition.name,
+            enum_member_names(&definition.docs, values)
+                .iter()
+                .zip(values)
+                .map(|(name, value)| format!(
+                    "{}{} = {},\n",
+                    self.indent(1),
+                    name,
+                    self.print_string(value)
+                ))
+                .collect::<String>()
+        )
+    }
+
+    /// The types extended by and the members of an interface declaring `jtype`,
+    /// if it is an object or an intersection of objects and references to objects.
+    fn interface_members(
+        &self,
+        jtype: &JavaScriptType,
+    ) -> Option<(
+        Vec<String>,
+        IndexMap<String, Property>,
+        AdditionalProperties,
+    )> {
+        match jtype {
+            JavaScriptType::AnonymousObject(o, AdditionalProperties::Allowed(_))
+                if o.is_empty() =>
+            {
+                None
+            }
+            JavaScriptType::AnonymousObject(o, additional) => {
+                Some((vec![], o.clone(), additional.clone()))
+            }
+            JavaScriptType::Product(parts) => {
+                let mut extends = vec![];
+                let mut rows = IndexMap::new();
+                let mut index = AdditionalProperties::Unspecified;
+                for part in parts {
+                    match part {
+                        JavaScriptType::Typename(t) if self.objects.contains(t) => {
+                            extends.push(t.clone())
+                        }
+                        // The objects are merged as long as they don't constrain the same properties.
+                        JavaScriptType::AnonymousObject(o, additional) => {
+                            if o.keys().any(|k| rows.contains_key(k)) {
+                                return None;
+                            }
+                            rows.extend(o.iter().map(|(k, p)| (k.clone(), p.clone())));
+                            index = match (index, additional) {
+                                (index, AdditionalProperties::Unspecified) => index,
+                                (AdditionalProperties::Unspecified, additional) => {
+                                    additional.clone()
+                                }
+                                _ => return None,
+                            };
+                        }
+                        _ => return None,
+                    }
+                }
+                Some((extends, rows, index))
+            }
+            _ => None,
+        }
+    }
+
+    /// `prefix` followed by the type and the terminator.
+    /// A union too long to fit in the line width, or with several variants spanning multiple lines,
+    /// starts on the next line with one variant per line.
+    fn print_member(&self, prefix: String, jtype: &JavaScriptType, level: usize) -> String {
+        let inline = self.print_type(jtype, level);
+        let column = prefix.chars().count() + 1 + inline.chars().count() + self.terminator().len();
+        let split = |variants: &[String]| {
+            if inline.contains('\n') {
+                variants.iter().filter(|v| v.contains('\n')).count() > 1
+            } else {
+                column > self.format.line_width
+            }
+        };
+        match self.print_variants(jtype, level + 1) {
+            Some(variants) if split(&variants) => format!(
+                "{}{}{}",
+                prefix,
+                variants
+                    .iter()
+                    .map(|v| format!("\n{}| {}", self.indent(level + 1), v))
+                    .collect::<String>(),
+                self.terminator()
+            ),
+            _ => format!("{} {}{}", prefix, inline, self.terminator()),
+        }
+    }
+
+    /// The variants of a union, if the type is one.
+    fn print_variants(&self, jtype: &JavaScriptType, level: usize) -> Option<Vec<String>> {
+        match jtype {
+            JavaScriptType::Sum(s) if s.len() > 1 => {
+                Some(s.iter().map(|t| self.print_type(t, level)).collect())
+            }
+            JavaScriptType::Nullable(t) => {
+                let mut variants = self
+                    .print_variants(t, level)
+                    .unwrap_or_else(|| vec![self.print_type(t, level)]);
+                variants.push("null".to_string());
+                Some(variants)
+            }
+            _ => None,
+        }
+    }
+
+    /// The JSDoc block followed by a newline, empty if there is nothing to document.
+    pub fn print_docs(&self, docs: &Documentation, level: usize) -> String {
+        let text = |t: &String| {
+            t.trim_end()
+                .lines()
+                .map(|l| l.trim_end().to_string())
+                .collect::<Vec<_>>()
+        };
+        let mut lines = docs.title.iter().flat_map(text).collect::<Vec<_>>();
+        if docs.title.is_some() && docs.description.is_some() {
+            lines.push(String::new());
+        }
+        lines.extend(docs.description.iter().flat_map(text));
+        if docs.deprecated {
+            lines.push("@deprecated".to_string());
+        }
+        lines.extend(
+            docs.default
+                .iter()
+                .map(|v| format!("@default {}", self.print_value(v))),
+        );
+        lines.extend(
+            docs.examples
+                .iter()
+                .map(|v| format!("@example {}", self.print_value(v))),
+        );
+        lines.extend(docs.format.iter().map(|f| format!("@format {}", f)));
+        lines.extend(
+            docs.constraints
+                .iter()
+                .map(|(tag, value)| format!("@{} {}", tag, value)),
+        );
+        if lines.is_empty() {
+            return String::new();
+        }
+        let indent = self.indent(level);
+        format!(
+            "{indent}/**\n{}{indent} */\n",
+            lines
+                .iter()
+                .map(|l| if l.is_empty() {
+                    format!("{} *\n", indent)
+                } else {
+                    format!("{} * {}\n", indent, l.replace("*/", "*\\/"))
+                })
+                .collect::<String>(),
+            indent = indent
+        )
+    }
+
+    /// A string literal, escaped so that it evaluates back to `s`.
+    pub fn print_string(&self, s: &str) -> String {
+        let quote = match self.format.quote {
+            Quote::Single => '\'',
+            Quote::Double => '"',
+        };
+        format!("{}{}{}", quote, escape(s, quote), quote)
+    }
+
+    /// Property names are only quoted if they aren't identifiers.
+    pub fn print_key(&self, key: &str) -> String {
+        if naming::is_identifier(key) {
+            key.to_string()
+        } else {
+            self.print_string(key)
+        }
+    }
+
+    pub fn print_value(&self, value: &JavaScriptValue) -> String {
+        match value {
+            JavaScriptValue::Null => "null".to_string(),
+            JavaScriptValue::String(s) => self.print_string(s),
+            JavaScriptValue::Boolean(b) => b.to_string(),
+            JavaScriptValue::Number(n) => n.to_string(),
+            JavaScriptValue::Array(v) => format!(
+                "[{}]",
+                v.iter()
+                    .map(|v| self.print_value(v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            JavaScriptValue::Object(o) if o.is_empty() => "{}".to_string(),
+            JavaScriptValue::Object(o) => format!(
+                "{{ {} }}",
+                o.iter()
+                    .map(|(k, v)| format!("{}: {}", self.print_key(k), self.print_value(v)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// The type, where `level` is the indentation of the line it starts on.
+    pub fn print_type(&self, jtype: &JavaScriptType, level: usize) -> String {
+        match jtype {
+            JavaScriptType::Array(t) => format!("{}[]", self.print_element(t, level)),
+            JavaScriptType::Tuple(elements, rest) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|t| self.print_type(t, level))
+                    .chain(
+                        rest.iter()
+                            .map(|t| format!("...{}[]", self.print_element(t, level)))
+                    )
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            JavaScriptType::AnonymousObject(o, AdditionalProperties::Allowed(t))
+                if o.is_empty() =>
+            {
+                format!("Record<string, {}>", self.print_type(t, level))
+            }
+            JavaScriptType::AnonymousObject(o, additional) => {
+                self.print_object(o, additional, level)
+            }
+            JavaScriptType::Product(p) => p
+                .iter()
+                .map(|t| self.print_intersected(t, level))
+                .collect::<Vec<_>>()
+                .join(" & "),
+            JavaScriptType::Sum(s) => s
+                .iter()
+                .map(|t| self.print_type(t, level))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            JavaScriptType::Typename(t) => t.to_string(),
+            JavaScriptType::Value(v) => self.print_value(v),
+            JavaScriptType::Nullable(t) => format!("{} | null", self.print_type(t, level)),
+            JavaScriptType::Not(t) => format!(
+                "unknown /* not {} */",
+                self.print_type(t, level).replace("*/", "*\\/")
+            ),
+            JavaScriptType::Branded(brand, _) => brand.to_string(),
+        }
+    }
+
+    /// The type as a part of an intersection, parenthesized if it is a union.
+    fn print_intersected(&self, jtype: &JavaScriptType, level: usize) -> String {
+        match jtype {
+            JavaScriptType::Sum(_) | JavaScriptType::Nullable(_) => {
+                format!("({})", self.print_type(jtype, level))
+            }
+            _ => self.print_type(jtype, level),
+        }
+    }
+
+    /// The type of the values of a brand, made distinct from its other values.
+    fn print_brand(&self, brand: &str, jtype: &JavaScriptType) -> String {
+        format!(
+            "{} & {{ readonly __brand: {} }}",
+            self.print_intersected(jtype, 0),
+            self.print_string(brand)
+        )
+    }
+
+    /// A type alias for every brand the definitions refer to, see [`brands`].
+    pub fn print_brands(&self, definitions: &[Definition]) -> Vec<String> {
+        brands(definitions)
+            .into_iter()
+            .map(|(brand, jtype)| {
+                format!(
+                    "export type {} = {}{}",
+                    brand,
+                    self.print_brand(brand, jtype),
+                    self.terminator()
+                )
+            })
+            .collect()
+    }
+
+    /// An object with braces, even if it only has an index signature.
+    fn print_object(
+        &self,
+        o: &IndexMap<String, Property>,
+        additional: &AdditionalProperties,
+        level: usize,
+    ) -> String {
+        let index = index_signature_type(o, additional);
+        if o.is_empty() && index.is_none() {
+            return "{}".to_string();
+        }
+        format!(
+            "{{\n{}{}}}",
+            o.iter()
+                .map(|(k, p)| {
+                    format!(
+                        "{}{}\n",
+                        self.print_docs(&p.docs, level + 1),
+                        self.print_member(
+                            format!(
+                                "{}{}{}:",
+                                self.indent(level + 1),
+                                self.print_key(k),
+                                if p.required { "" } else { "?" }
+                            ),
+                            &p.jtype,
+                            level + 1
+                        )
+                    )
+                })
+                .chain(index.map(|t| {
+                    format!(
+                        "{}\n",
+                        self.print_member(
+                            format!("{}[key: string]:", self.indent(level + 1)),
+                            &t,
+                            level + 1
+                        )
+                    )
+                }))
+                .collect::<String>(),
+            self.indent(level)
+        )
+    }
+
+    /// The element type of an array, parenthesized unless it binds tighter than `[]`.
+    fn print_element(&self, jtype: &JavaScriptType, level: usize) -> String {
+        match jtype {
+            JavaScriptType::Sum(_)
+            | JavaScriptType::Product(_)
+            | JavaScriptType::Nullable(_)
+            | JavaScriptType::Not(_) => format!("({})", self.print_type(jtype, level)),
+            _ => self.print_type(jtype, level),
+        }
+    }
+}
+
+/// The names of the members of an enum of `values`, from `x-enum-varnames`/`x-enumNames` if given for every value,
+/// otherwise after the values themselves.
+fn enum_member_names(docs: &Documentation, values: &[&str]) -> Vec<String> {
+    let names = &docs.enum_names;
+    let names = if names.len() == values.len() {
+        names
+            .iter()
+            .map(|n| {
+                if naming::is_identifier(n) {
+                    n.clone()
+                } else {
+                    naming::identifier(n)
+                }
+            })
+            .collect()
+    } else {
+        values.iter().map(|v| naming::identifier(v)).collect()
+    };
+    naming::deduplicate(names)
+}
+
+/// Escapes `s` for a string literal delimited by `quote`.
+/// Line terminators, including the unicode separators, and other control characters are escaped too
+/// so that the literal stays on one line.
+fn escape(s: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The type of the index signature of an object, if it has one.
+/// TypeScript requires every named property to be assignable to it,
+/// so it is widened with the type of each of them.
+fn index_signature_type(
+    rows: &IndexMap<String, Property>,
+    additional: &AdditionalProperties,
+) -> Option<JavaScriptType> {
+    let t = match additional {
+        AdditionalProperties::Allowed(t) => t.as_ref(),
+        _ => return None,
+    };
+    if matches!(t, JavaScriptType::Typename(n) if n == "any" || n == "unknown") {
+        return Some(t.clone());
+    }
+    let mut types = vec![t.clone()];
+    let optional = rows.values().any(|p| !p.required);
+    let widened = rows
+        .values()
+        .map(|p| p.jtype.clone())
+        .chain(optional.then(|| JavaScriptType::typename("undefined")));
+    for t in widened {
+        if !types.iter().any(|x| x.to_string() == t.to_string()) {
+            types.push(t);
+        }
+    }
+    Some(if types.len() == 1 {
+        types.remove(0)
+    } else {
+        JavaScriptType::Sum(types)
+    })
+}
+
+/// Whether an interface can extend the type, `objects` being the definitions known to be objects.
+fn is_object_type(jtype: &JavaScriptType, objects: &HashSet<String>) -> bool {
+    match jtype {
+        JavaScriptType::AnonymousObject(_, _) => true,
+        JavaScriptType::Typename(t) => objects.contains(t),
+        JavaScriptType::Product(p) => p.iter().all(|t| is_object_type(t, objects)),
+        _ => false,
+    }
+}
@@ -0,0 +1,519 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use indexmap::IndexMap;
+
+use super::escape;
+use crate::ir::{dependency_order, is_recursive, Additional, Definition, Field, Kind, Type};
+use crate::naming;
+use crate::repr::{Documentation, JavaScriptValue};
+
+const INDENT: &str = "    ";
+
+/// What objects are declared as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Classes {
+    /// `TypedDict` classes, with `NotRequired` properties.
+    TypedDict,
+    /// pydantic `BaseModel` classes, with optional properties defaulting to `None`.
+    Pydantic,
+}
+
+struct Module<'a> {
+    definitions: HashMap<&'a str, &'a Definition>,
+    classes: Classes,
+    /// The names to import, by the module they are imported from.
+    imports: RefCell<BTreeSet<(&'static str, &'static str)>>,
+}
+
+/// A Python module declaring every definition, after the ones it refers to.
+/// Objects are declared as classes, the other definitions as type aliases.
+/// The definitions of a cycle of references refer to the ones declared after them by name, as forward references.
+pub fn print_python(definitions: &[Definition], classes: Classes) -> String {
+    let module = Module {
+        definitions: definitions.iter().map(|d| (d.name.as_str(), d)).collect(),
+        classes,
+        imports: RefCell::new(BTreeSet::new()),
+    };
+    let mut declared = HashSet::new();
+    let mut items = vec![];
+    for group in dependency_order(definitions) {
+        let mut rebuilt = vec![];
+        for definition in group.iter() {
+            let forward = group
+                .iter()
+                .map(|d| d.name.as_str())
+                .filter(|name| !declared.contains(name))
+                .collect::<HashSet<_>>();
+            if module.is_class(&definition.schema, &mut HashSet::new()) {
+                items.push(module.print_class(definition, &forward));
+                // pydantic resolves the forward references of a model once they are all declared.
+                if classes == Classes::Pydantic && is_recursive(&group) {
+                    rebuilt.push(format!("{}.model_rebuild()", definition.name));
+                }
+            } else {
+                items.push(format!(
+                    "{} = {}\n{}",
+                    definition.name,
+                    module.python_type(&definition.schema, &forward),
+                    print_docs(&definition.schema.meta.docs, 0)
+                ));
+            }
+            declared.insert(definition.name.as_str());
+        }
+        if !rebuilt.is_empty() {
+            items.push(format!("{}\n", rebuilt.join("\n")));
+        }
+    }
+    let imports = module.imports.into_inner();
+    let mut modules = IndexMap::<_, Vec<_>>::new();
+    for module in ["typing", "typing_extensions", "pydantic"].iter() {
+        let names = imports
+            .iter()
+            .filter(|(m, _)| m == module)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        if !names.is_empty() {
+            modules.insert(*module, names);
+        }
+    }
+    format!(
+        "{}{}",
+        modules
+            .iter()
+            .map(|(module, names)| format!("from {} import {}\n", module, names.join(", ")))
+            .chain((!modules.is_empty()).then(|| "\n\n".to_string()))
+            .collect::<String>(),
+        items
+            .iter()
+            .map(|item| item.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n\n\n")
+    )
+}
+
+impl<'a> Module<'a> {
+    fn import(&self, module: &'static str, name: &'static str) -> &'static str {
+        self.imports.borrow_mut().insert((module, name));
+        name
+    }
+
+    fn print_class(&self, definition: &Definition, forward: &HashSet<&str>) -> String {
+        let parts = definition.schema.intersected();
+        let bases = parts
+            .iter()
+            .filter_map(|part| match &part.kind {
+                Kind::Reference(name) if !part.meta.nullable => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let inherited = bases
+            .iter()
+            .flat_map(|base| self.object_rows(base, &mut HashSet::new()))
+            .collect::<IndexMap<_, _>>();
+        let mut rows = IndexMap::new();
+        let mut additional = &Additional::Unspecified;
+        for part in parts.iter() {
+            match &part.kind {
+                Kind::Object(fields, a) if !part.meta.nullable => {
+                    rows.extend(fields.iter().map(|(k, f)| (k.clone(), f.clone())));
+                    if !matches!(a, Additional::Unspecified) {
+                        additional = a;
+                    }
+                }
+                _ => (),
+            }
+        }
+        match self.classes {
+            Classes::TypedDict => {
+                // A TypedDict can't redefine the properties it inherits, e.g. to narrow a discriminator.
+                rows.retain(|k, _| !inherited.contains_key(k));
+                if rows.keys().all(|k| naming::is_python_identifier(k)) {
+                    let bases = if bases.is_empty() {
+                        vec![self.import("typing_extensions", "TypedDict")]
+                    } else {
+                        bases
+                    };
+                    self.print_class_statement(definition, &bases, vec![], &rows, forward)
+                } else {
+                    // Properties which aren't identifiers need the functional syntax, which can't inherit.
+                    let rows = inherited
+                        .into_iter()
+                        .chain(rows)
+                        .collect::<IndexMap<_, _>>();
+                    format!(
+                        "{} = {}({}, {{\n{}}})\n{}",
+                        definition.name,
+                        self.import("typing_extensions", "TypedDict"),
+                        print_string(&definition.name),
+                        rows.iter()
+                            .map(|(k, f)| format!(
+                                "{}{}: {},\n",
+                                INDENT,
+                                print_string(k),
+                                self.row_type(f, forward)
+                            ))
+                            .collect::<String>(),
+                        print_docs(&definition.schema.meta.docs, 0)
+                    )
+                }
+            }
+            Classes::Pydantic => {
+                let bases = if bases.is_empty() {
+                    vec![self.import("pydantic", "BaseModel")]
+                } else {
+                    bases
+                };
+                let config = match additional {
+                    Additional::Unspecified => vec![],
+                    Additional::Forbidden => vec![format!(
+                        "model_config = {}(extra=\"forbid\")",
+                        self.import("pydantic", "ConfigDict")
+                    )],
+                    Additional::Allowed(_) => vec![format!(
+                        "model_config = {}(extra=\"allow\")",
+                        self.import("pydantic", "ConfigDict")
+                    )],
+                };
+                self.print_class_statement(definition, &bases, config, &rows, forward)
+            }
+        }
+    }
+
+    fn print_class_statement(
+        &self,
+        definition: &Definition,
+        bases: &[&str],
+        config: Vec<String>,
+        rows: &IndexMap<String, Field>,
+        forward: &HashSet<&str>,
+    ) -> String {
+        let names = naming::deduplicate(
+            rows.keys()
+                .map(|k| match self.classes {
+                    Classes::TypedDict => k.to_string(),
+                    Classes::Pydantic => naming::python_field_name(k),
+                })
+                .collect(),
+        );
+        let body = print_docs(&definition.schema.meta.docs, 1)
+            + &config
+                .iter()
+                .map(|line| format!("{}{}\n", INDENT, line))
+                .collect::<String>()
+            + &rows
+                .iter()
+                .zip(names)
+                .map(|((k, f), name)| {
+                    format!(
+                        "{}{}: {}\n{}",
+                        INDENT,
+                        name,
+                        self.print_field(k, &name, f, forward),
+                        print_docs(&f.schema.meta.docs, 1)
+                    )
+                })
+                .collect::<String>();
+        format!(
+            "class {}({}):\n{}",
+            definition.name,
+            bases.join(", "),
+            if body.is_empty() {
+                format!("{}pass\n", INDENT)
+            } else {
+                body
+            }
+        )
+    }
+
+    /// The annotation of an attribute declaring the property `key`, followed by its default if any.
+    fn print_field(&self, key: &str, name: &str, field: &Field, forward: &HashSet<&str>) -> String {
+        match self.classes {
+            Classes::TypedDict => self.row_type(field, forward),
+            Classes::Pydantic => {
+                let schema = &field.schema;
+                let (annotation, default) = match (schema.meta.nullable, field.required) {
+                    (_, true) => (self.python_type(schema, forward), None),
+                    (true, false) => (self.python_type(schema, forward), Some("None")),
+                    (false, false) => (
+                        format!(
+                            "{}[{}]",
+                            self.import("typing", "Optional"),
+                            self.python_type(schema, forward)
+                        ),
+                        Some("None"),
+                    ),
+                };
+                match (name == key, default) {
+                    (true, None) => annotation,
+                    (true, Some(default)) => format!("{} = {}", annotation, default),
+                    (false, default) => format!(
+                        "{} = {}({}alias={})",
+                        annotation,
+                        self.import("pydantic", "Field"),
+                        default.map(|d| format!("{}, ", d)).unwrap_or_default(),
+                        print_string(key)
+                    ),
+                }
+            }
+        }
+    }
+
+    /// The type of a property of a TypedDict, `NotRequired` if it isn't required.
+    fn row_type(&self, field: &Field, forward: &HashSet<&str>) -> String {
+        let ptype = self.python_type(&field.schema, forward);
+        if field.required {
+            ptype
+        } else {
+            format!(
+                "{}[{}]",
+                self.import("typing_extensions", "NotRequired"),
+                ptype
+            )
+        }
+    }
+
+    /// The type of a value, an `int` if it is an integer or a number formatted as one.
+    /// The definitions in `forward` aren't declared yet, so they are referred to by name.
+    fn python_type(&self, ty: &Type, forward: &HashSet<&str>) -> String {
+        if ty.meta.nullable {
+            return format!(
+                "{}[{}]",
+                self.import("typing", "Optional"),
+                self.python_type(&ty.non_null(), forward)
+            );
+        }
+        match &ty.kind {
+            Kind::String | Kind::Date => "str".to_string(),
+            Kind::Integer => "int".to_string(),
+            Kind::Number => match ty.meta.docs.format.as_deref() {
+                Some("int32") | Some("int64") => "int",
+                _ => "float",
+            }
+            .to_string(),
+            Kind::Boolean => "bool".to_string(),
+            Kind::Never => "None".to_string(),
+            Kind::Reference(name) | Kind::Mapped(name) | Kind::Other(name) => match name.as_str() {
+                "bigint" => "int".to_string(),
+                "undefined" | "never" => "None".to_string(),
+                name if forward.contains(name) => print_string(name),
+                name if self.definitions.contains_key(name) => name.to_string(),
+                _ => self.import("typing", "Any").to_string(),
+            },
+            Kind::Array(t) => format!(
+                "{}[{}]",
+                self.import("typing", "List"),
+                self.python_type(t, forward)
+            ),
+            Kind::Tuple(elements, None) => format!(
+                "{}[{}]",
+                self.import("typing", "Tuple"),
+                if elements.is_empty() {
+                    "()".to_string()
+                } else {
+                    elements
+                        .iter()
+                        .map(|t| self.python_type(t, forward))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            ),
+            Kind::Tuple(_, Some(_)) => format!(
+                "{}[{}]",
+                self.import("typing", "List"),
+                self.import("typing", "Any")
+            ),
+            Kind::Object(fields, additional) => format!(
+                "{}[str, {}]",
+                self.import("typing", "Dict"),
+                match additional {
+                    Additional::Allowed(t) if fields.is_empty() => {
+                        self.python_type(t, forward)
+                    }
+                    _ => self.import("typing", "Any").to_string(),
+                }
+            ),
+            Kind::Null | Kind::Const(_) | Kind::Enum(_) | Kind::Union(_) => {
+                let variants = match &ty.kind {
+                    Kind::Enum(values) => values.iter().cloned().map(Variant::Value).collect(),
+                    Kind::Union(variants) => variants.iter().map(Variant::Type).collect(),
+                    _ => vec![Variant::Type(ty)],
+                };
+                let literals = variants
+                    .iter()
+                    .filter_map(|v| v.value().and_then(print_literal))
+                    .collect::<Vec<_>>();
+                let mut types = vec![];
+                if !literals.is_empty() {
+                    types.push(format!(
+                        "{}[{}]",
+                        self.import("typing", "Literal"),
+                        literals.join(", ")
+                    ));
+                }
+                for variant in variants.iter() {
+                    let ptype = match (variant.value(), variant) {
+                        (Some(v), _) if print_literal(v).is_some() => continue,
+                        (Some(v), _) => match v {
+                            JavaScriptValue::Null => "None".to_string(),
+                            JavaScriptValue::Number(_) => "float".to_string(),
+                            _ => self.import("typing", "Any").to_string(),
+                        },
+                        (None, Variant::Type(t)) if matches!(t.kind, Kind::Null) => {
+                            "None".to_string()
+                        }
+                        (None, Variant::Type(t)) => self.python_type(t, forward),
+                        (None, Variant::Value(_)) => unreachable!(),
+                    };
+                    if !types.contains(&ptype) {
+                        types.push(ptype);
+                    }
+                }
+                if types.len() == 1 {
+                    types.remove(0)
+                } else {
+                    format!("{}[{}]", self.import("typing", "Union"), types.join(", "))
+                }
+            }
+            // The variants of a tagged union are the types they narrow.
+            Kind::Intersection(_) | Kind::Tagged { .. } => {
+                let parts = ty.intersected();
+                let types = parts
+                    .iter()
+                    .filter(|part| part.meta.nullable || !matches!(part.kind, Kind::Object(..)))
+                    .collect::<Vec<_>>();
+                match types.as_slice() {
+                    [t] => self.python_type(t, forward),
+                    _ => self.import("typing", "Any").to_string(),
+                }
+            }
+            Kind::Any | Kind::Unknown | Kind::Not(_) => self.import("typing", "Any").to_string(),
+            Kind::Branded(_, t) => self.python_type(t, forward),
+        }
+    }
+
+    /// Whether `ty` is declared as a class, i.e. it is an object which isn't a map,
+    /// or an intersection of objects and classes.
+    fn is_class(&self, ty: &'a Type, visited: &mut HashSet<&'a str>) -> bool {
+        match &ty.kind {
+            _ if ty.meta.nullable => false,
+            Kind::Object(fields, additional) => {
+                !fields.is_empty() || matches!(additional, Additional::Forbidden)
+            }
+            Kind::Intersection(_) | Kind::Tagged { .. } => {
+                ty.intersected().into_iter().all(|part| match part {
+                    // The object narrowing the discriminator of a variant.
+                    Cow::Owned(_) => true,
+                    Cow::Borrowed(part) => match &part.kind {
+                        _ if part.meta.nullable => false,
+                        Kind::Object(..) => true,
+                        Kind::Reference(name) => {
+                            visited.insert(name)
+                                && self
+                                    .definitions
+                                    .get(name.as_str())
+                                    .is_some_and(|d| self.is_class(&d.schema, visited))
+                        }
+                        _ => false,
+                    },
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// The properties of the class `name`, including the ones it inherits.
+    fn object_rows(&self, name: &str, visited: &mut HashSet<&'a str>) -> Vec<(String, Field)> {
+        let definition = match self.definitions.get_key_value(name) {
+            Some((name, definition)) if visited.insert(name) => *definition,
+            _ => return vec![],
+        };
+        let mut rows = vec![];
+        for part in definition.schema.intersected() {
+            match &part.kind {
+                _ if part.meta.nullable => (),
+                Kind::Reference(base) => rows.extend(self.object_rows(base, visited)),
+                Kind::Object(fields, _) => {
+                    rows.extend(fields.iter().map(|(k, f)| (k.clone(), f.clone())))
+                }
+                _ => (),
+            }
+        }
+        rows
+    }
+}
+
+/// A variant of a union, which is a literal if it is a value of an `enum` or a `const`.
+enum Variant<'a> {
+    Type(&'a Type),
+    Value(JavaScriptValue),
+}
+
+impl Variant<'_> {
+    fn value(&self) -> Option<&JavaScriptValue> {
+        match self {
+            Variant::Type(Type {
+                kind: Kind::Const(value),
+                meta,
+            }) if !meta.nullable => Some(value),
+            Variant::Type(_) => None,
+            Variant::Value(value) => Some(value),
+        }
+    }
+}
+
+/// A string literal, escaped so that it evaluates back to `s`.
+fn print_string(s: &str) -> String {
+    format!("\"{}\"", escape(s, '"'))
+}
+
+/// The value as an argument of `Literal`, which only takes strings, integers, booleans and `None`.
+fn print_literal(value: &JavaScriptValue) -> Option<String> {
+    match value {
+        JavaScriptValue::String(s) => Some(print_string(s)),
+        JavaScriptValue::Number(n) if n.fract() == 0.0 && n.is_finite() => {
+            Some(format!("{}", *n as i64))
+        }
+        JavaScriptValue::Boolean(true) => Some("True".to_string()),
+        JavaScriptValue::Boolean(false) => Some("False".to_string()),
+        _ => None,
+    }
+}
+
+/// The docstring of a class, an attribute or an alias followed by a newline, empty if there is nothing to document.
+fn print_docs(docs: &Documentation, level: usize) -> String {
+    let text = |t: &String| {
+        t.trim_end()
+            .lines()
+            .map(|l| {
+                l.trim_end()
+                    .replace('\\', "\\\\")
+                    .replace("\"\"\"", "\\\"\\\"\\\"")
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut lines = docs.title.iter().flat_map(text).collect::<Vec<_>>();
+    if docs.title.is_some() && docs.description.is_some() {
+        lines.push(String::new());
+    }
+    lines.extend(docs.description.iter().flat_map(text));
+    let indent = INDENT.repeat(level);
+    match lines.as_slice() {
+        [] => String::new(),
+        // A quote right before the closing ones would end the docstring early.
+        [line] if !line.ends_with('"') => format!("{}\"\"\"{}\"\"\"\n", indent, line),
+        [first, rest @ ..] => format!(
+            "{indent}\"\"\"{}\n{}{indent}\"\"\"\n",
+            first,
+            rest.iter()
+                .map(|l| if l.is_empty() {
+                    "\n".to_string()
+                } else {
+                    format!("{}{}\n", indent, l)
+                })
+                .collect::<String>(),
+            indent = indent
+        ),
+    }
+}
@@ -0,0 +1,603 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use super::enum_member_names;
+use crate::ir::{self, dependency_order, is_recursive, Additional, Definition, Kind, Type};
+use crate::naming;
+use crate::repr::{Documentation, JavaScriptValue};
+
+/// The traits every generated type implements.
+const DERIVE: &str = "#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]";
+
+const INDENT: &str = "    ";
+
+/// What the Rust type of a definition may refer to.
+struct Scope<'a> {
+    definitions: &'a HashMap<&'a str, &'a Definition>,
+    /// The discriminators of the tagged unions each definition is a variant of.
+    tags: &'a HashMap<&'a str, HashSet<&'a str>>,
+    /// The definitions of the same cycle of references as the one being declared, which are boxed.
+    cycle: &'a HashSet<&'a str>,
+}
+
+/// The values identifying a variant of a tagged union, and what the variant carries.
+type TaggedVariant<'a> = (Vec<&'a str>, &'a Type);
+
+/// A field of a struct or of a variant of an enum.
+struct Field {
+    docs: String,
+    /// The property it is (de)serialized from, if it isn't flattened.
+    key: Option<String>,
+    attributes: Vec<String>,
+    name: String,
+    rtype: String,
+}
+
+/// Rust structs and enums deriving serde's `Serialize` and `Deserialize` for every definition.
+/// References to a definition of the same cycle of references are boxed, unless in a `Vec` or a `HashMap`.
+/// Types Rust can't express, e.g. unions nested in properties, are `serde_json::Value`.
+pub fn print_rust(definitions: &[Definition]) -> String {
+    let by_name = definitions
+        .iter()
+        .map(|d| (d.name.as_str(), d))
+        .collect::<HashMap<_, _>>();
+    let mut cycles = HashMap::new();
+    for group in dependency_order(definitions) {
+        if is_recursive(&group) {
+            let names = group
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<HashSet<_>>();
+            for definition in group {
+                cycles.insert(definition.name.as_str(), names.clone());
+            }
+        }
+    }
+    let mut tags = HashMap::<_, HashSet<_>>::new();
+    for (tag, variants) in definitions
+        .iter()
+        .filter_map(|d| tagged_variants(&d.schema))
+    {
+        for (_, payload) in variants {
+            if let Kind::Reference(name) = &payload.kind {
+                tags.entry(name.as_str()).or_default().insert(tag);
+            }
+        }
+    }
+    let acyclic = HashSet::new();
+    let items = definitions
+        .iter()
+        .map(|definition| {
+            let scope = Scope {
+                definitions: &by_name,
+                tags: &tags,
+                cycle: cycles.get(definition.name.as_str()).unwrap_or(&acyclic),
+            };
+            print_definition(definition, &scope)
+        })
+        .collect::<Vec<_>>();
+    let mut imports = String::new();
+    if items.iter().any(|item| item.contains(DERIVE)) {
+        imports.push_str("use serde::{Deserialize, Serialize};\n");
+    }
+    if items.iter().any(|item| item.contains("HashMap<")) {
+        imports.push_str("use std::collections::HashMap;\n");
+    }
+    format!("{}\n{}", imports, items.join("\n\n"))
+}
+
+fn print_definition(definition: &Definition, scope: &Scope) -> String {
+    let name = &definition.name;
+    let docs = &definition.schema.meta.docs;
+    let non_null = definition.schema.non_null();
+    let ty = match &definition.schema.kind {
+        // Rust can't declare a nullable struct or enum, which are declared as if they weren't.
+        Kind::Union(_) | Kind::Enum(_) => &non_null,
+        _ if is_struct(&non_null, scope) => &non_null,
+        _ => &definition.schema,
+    };
+    if let Some(values) = ty.string_enum_values() {
+        let names = enum_member_names(docs, &values)
+            .iter()
+            .map(|n| variant_name(n))
+            .collect();
+        let variants = naming::deduplicate(names)
+            .into_iter()
+            .zip(values)
+            .map(|(variant, value)| {
+                format!(
+                    "{}{}{},\n",
+                    print_attributes(&rename(&variant, value), 1),
+                    INDENT,
+                    variant
+                )
+            })
+            .collect::<String>();
+        return format!(
+            "{}{}\npub enum {} {{\n{}}}",
+            print_docs(docs, 0),
+            DERIVE,
+            name,
+            variants
+        );
+    }
+    if let Some((tag, variants)) = tagged_variants(ty) {
+        let names = variants
+            .iter()
+            .map(|(values, payload)| match &payload.kind {
+                Kind::Reference(t) => t.clone(),
+                _ => variant_name(values[0]),
+            })
+            .collect();
+        let variants = naming::deduplicate(names)
+            .into_iter()
+            .zip(variants)
+            .map(|(variant, (values, payload))| {
+                let mut attributes = rename(&variant, values[0]);
+                attributes.extend(values[1..].iter().map(|v| format!("alias = {:?}", v)));
+                format!(
+                    "{}{}",
+                    print_attributes(&attributes, 1),
+                    print_variant(&variant, payload, &std::iter::once(tag).collect(), scope)
+                )
+            })
+            .collect::<String>();
+        return format!(
+            "{}{}\n#[serde(tag = {:?})]\npub enum {} {{\n{}}}",
+            print_docs(docs, 0),
+            DERIVE,
+            tag,
+            name,
+            variants
+        );
+    }
+    match &ty.kind {
+        Kind::Union(variants)
+            if !variants
+                .iter()
+                .all(|v| !v.meta.nullable && matches!(v.kind, Kind::Const(_) | Kind::Null)) =>
+        {
+            let names = variants.iter().map(untagged_variant_name).collect();
+            let variants = naming::deduplicate(names)
+                .into_iter()
+                .zip(variants)
+                .map(|(variant, payload)| print_variant(&variant, payload, &HashSet::new(), scope))
+                .collect::<String>();
+            format!(
+                "{}{}\n#[serde(untagged)]\npub enum {} {{\n{}}}",
+                print_docs(docs, 0),
+                DERIVE,
+                name,
+                variants
+            )
+        }
+        _ if is_struct(ty, scope) => {
+            let skip = scope.tags.get(name.as_str()).cloned().unwrap_or_default();
+            let (fields, strict) = struct_fields(ty, &skip, scope);
+            format!(
+                "{}{}\n{}pub struct {} {{\n{}}}",
+                print_docs(docs, 0),
+                DERIVE,
+                if strict {
+                    "#[serde(deny_unknown_fields)]\n"
+                } else {
+                    ""
+                },
+                name,
+                print_fields(&fields, 1, true)
+            )
+        }
+        _ => format!(
+            "{}pub type {} = {};",
+            print_docs(docs, 0),
+            name,
+            rust_type(ty, true, scope)
+        ),
+    }
+}
+
+/// A variant of an enum carrying `payload`, with the fields of an object inline.
+fn print_variant(variant: &str, payload: &Type, skip: &HashSet<&str>, scope: &Scope) -> String {
+    match &payload.kind {
+        Kind::Null | Kind::Const(JavaScriptValue::Null) => format!("{}{},\n", INDENT, variant),
+        Kind::Object(fields, _) if !fields.is_empty() && !payload.meta.nullable => {
+            let (fields, _) = struct_fields(payload, skip, scope);
+            format!(
+                "{}{} {{\n{}{}}},\n",
+                INDENT,
+                variant,
+                print_fields(&fields, 2, false),
+                INDENT
+            )
+        }
+        _ => format!(
+            "{}{}({}),\n",
+            INDENT,
+            variant,
+            rust_type(payload, true, scope)
+        ),
+    }
+}
+
+/// `rename = ...` if the Rust `name` isn't the same as the `key` it is (de)serialized from.
+fn rename(name: &str, key: &str) -> Vec<String> {
+    if name.trim_start_matches("r#") == key {
+        vec![]
+    } else {
+        vec![format!("rename = {:?}", key)]
+    }
+}
+
+/// A `#[serde(...)]` attribute followed by a newline, empty if there are no `attributes`.
+fn print_attributes(attributes: &[String], level: usize) -> String {
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "{}#[serde({})]\n",
+            INDENT.repeat(level),
+            attributes.join(", ")
+        )
+    }
+}
+
+fn print_fields(fields: &[Field], level: usize, public: bool) -> String {
+    let indent = INDENT.repeat(level);
+    fields
+        .iter()
+        .map(|field| {
+            let mut attributes = field
+                .key
+                .as_ref()
+                .map(|key| rename(&field.name, key))
+                .unwrap_or_default();
+            attributes.extend(field.attributes.iter().cloned());
+            format!(
+                "{}{}{}{}: {},\n",
+                field.docs,
+                print_attributes(&attributes, level),
+                indent,
+                if public {
+                    format!("pub {}", field.name)
+                } else {
+                    field.name.clone()
+                },
+                field.rtype
+            )
+        })
+        .collect()
+}
+
+/// The fields of a struct declaring an object or an intersection of objects,
+/// and whether it denies unknown fields.
+/// Referenced objects are flattened into it, and the properties in `skip` or already in them are left out.
+fn struct_fields(ty: &Type, skip: &HashSet<&str>, scope: &Scope) -> (Vec<Field>, bool) {
+    let parts = ty.intersected();
+    let flattened = parts
+        .iter()
+        .filter(|part| !part.meta.nullable && matches!(part.kind, Kind::Reference(_)))
+        .flat_map(|part| object_keys(part, scope, &mut HashSet::new()))
+        .collect::<HashSet<_>>();
+    let mut fields = vec![];
+    let mut strict = false;
+    for part in parts.iter() {
+        match &part.kind {
+            Kind::Object(properties, additional) if !part.meta.nullable => {
+                fields.extend(
+                    properties
+                        .iter()
+                        .filter(|(k, _)| {
+                            !skip.contains(k.as_str()) && !flattened.contains(k.as_str())
+                        })
+                        .map(|(k, f)| property_field(k, f, scope)),
+                );
+                match additional {
+                    Additional::Unspecified => (),
+                    Additional::Forbidden => strict = true,
+                    Additional::Allowed(t) => fields.push(Field {
+                        docs: String::new(),
+                        key: None,
+                        attributes: vec!["flatten".to_string()],
+                        name: "additional_properties".to_string(),
+                        rtype: format!("HashMap<String, {}>", rust_type(t, false, scope)),
+                    }),
+                }
+            }
+            kind => fields.push(Field {
+                docs: String::new(),
+                key: None,
+                attributes: vec!["flatten".to_string()],
+                name: match kind {
+                    Kind::Reference(t) if !part.meta.nullable => naming::rust_field_name(t),
+                    _ => "value".to_string(),
+                },
+                rtype: rust_type(part, true, scope),
+            }),
+        }
+    }
+    let names = naming::deduplicate(fields.iter().map(|f| f.name.clone()).collect());
+    for (field, name) in fields.iter_mut().zip(names) {
+        field.name = name;
+    }
+    // serde doesn't support denying unknown fields along with flattened ones.
+    let strict = strict && !fields.iter().any(|f| f.key.is_none());
+    (fields, strict)
+}
+
+/// A field for a property, optional if it isn't required.
+fn property_field(key: &str, field: &ir::Field, scope: &Scope) -> Field {
+    let schema = &field.schema;
+    let (ty, optional) = match (schema.meta.nullable, field.required) {
+        // Rust can't tell a missing property from a null one.
+        (true, false) => (Cow::Owned(schema.non_null()), true),
+        (_, required) => (Cow::Borrowed(schema), !required),
+    };
+    let rtype = rust_type(&ty, true, scope);
+    Field {
+        docs: print_docs(&schema.meta.docs, 1),
+        key: Some(key.to_string()),
+        attributes: if optional {
+            vec![
+                "default".to_string(),
+                "skip_serializing_if = \"Option::is_none\"".to_string(),
+            ]
+        } else {
+            vec![]
+        },
+        name: naming::rust_field_name(key),
+        rtype: if optional {
+            format!("Option<{}>", rtype)
+        } else {
+            rtype
+        },
+    }
+}
+
+/// The Rust type of a field, an element or a payload, sized after the `format` of the schema.
+/// References to the definitions of the cycle are boxed if `boxed`.
+fn rust_type(ty: &Type, boxed: bool, scope: &Scope) -> String {
+    if ty.meta.nullable {
+        return format!("Option<{}>", rust_type(&ty.non_null(), boxed, scope));
+    }
+    match &ty.kind {
+        Kind::String | Kind::Date => "String".to_string(),
+        Kind::Integer => match ty.meta.docs.format.as_deref() {
+            Some("int32") => "i32",
+            _ => "i64",
+        }
+        .to_string(),
+        Kind::Number => match ty.meta.docs.format.as_deref() {
+            Some("int32") => "i32",
+            Some("int64") => "i64",
+            Some("float") => "f32",
+            _ => "f64",
+        }
+        .to_string(),
+        Kind::Boolean => "bool".to_string(),
+        Kind::Never => "()".to_string(),
+        Kind::Reference(name) | Kind::Mapped(name) | Kind::Other(name) => match name.as_str() {
+            "bigint" => "i64".to_string(),
+            "undefined" | "never" => "()".to_string(),
+            name if boxed && scope.cycle.contains(name) => format!("Box<{}>", name),
+            name if scope.definitions.contains_key(name) => name.to_string(),
+            _ => "serde_json::Value".to_string(),
+        },
+        Kind::Branded(_, t) => rust_type(t, boxed, scope),
+        Kind::Array(t) => format!("Vec<{}>", rust_type(t, false, scope)),
+        Kind::Tuple(elements, None) => format!(
+            "({}{})",
+            elements
+                .iter()
+                .map(|t| rust_type(t, boxed, scope))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if elements.len() == 1 { "," } else { "" }
+        ),
+        Kind::Object(fields, additional) if fields.is_empty() => format!(
+            "HashMap<String, {}>",
+            match additional {
+                Additional::Allowed(t) => rust_type(t, false, scope),
+                _ => "serde_json::Value".to_string(),
+            }
+        ),
+        Kind::Null => "()".to_string(),
+        Kind::Const(value) => value_type(value).to_string(),
+        // A union of literals of the same type is that type, e.g. `1 | 2`.
+        Kind::Enum(values) => match values
+            .iter()
+            .map(value_type)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [rtype] => rtype.to_string(),
+            _ => "serde_json::Value".to_string(),
+        },
+        Kind::Union(variants) => {
+            let types = variants
+                .iter()
+                .map(|t| rust_type(t, boxed, scope))
+                .collect::<HashSet<_>>();
+            match types.into_iter().collect::<Vec<_>>().as_slice() {
+                [rtype] => rtype.clone(),
+                _ => "serde_json::Value".to_string(),
+            }
+        }
+        Kind::Intersection(parts) if parts.len() == 1 => rust_type(&parts[0], boxed, scope),
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+/// The Rust type of a literal.
+fn value_type(value: &JavaScriptValue) -> &'static str {
+    match value {
+        JavaScriptValue::String(_) => "String",
+        JavaScriptValue::Number(_) => "f64",
+        JavaScriptValue::Boolean(_) => "bool",
+        JavaScriptValue::Null => "()",
+        JavaScriptValue::Array(_) | JavaScriptValue::Object(_) => "serde_json::Value",
+    }
+}
+
+/// Whether `ty` is declared as a struct, i.e. it is an object which isn't a map,
+/// or an intersection of objects.
+fn is_struct(ty: &Type, scope: &Scope) -> bool {
+    match &ty.kind {
+        _ if ty.meta.nullable => false,
+        Kind::Object(fields, additional) => {
+            !fields.is_empty() || !matches!(additional, Additional::Allowed(_))
+        }
+        Kind::Intersection(_) | Kind::Tagged { .. } => is_object(ty, scope, &mut HashSet::new()),
+        _ => false,
+    }
+}
+
+/// Whether `ty` is an object, a reference to one or an intersection of them.
+fn is_object<'a>(ty: &'a Type, scope: &Scope<'a>, visited: &mut HashSet<&'a str>) -> bool {
+    match &ty.kind {
+        _ if ty.meta.nullable => false,
+        Kind::Object(..) => true,
+        Kind::Reference(name) => {
+            visited.insert(name)
+                && scope
+                    .definitions
+                    .get(name.as_str())
+                    .is_some_and(|d| is_object(&d.schema, scope, visited))
+        }
+        Kind::Intersection(parts) => parts.iter().all(|p| is_object(p, scope, visited)),
+        // The discriminator of a variant is narrowed by an object.
+        Kind::Tagged { variant, .. } => is_object(variant, scope, visited),
+        _ => false,
+    }
+}
+
+/// The properties of an object, a reference to one or an intersection of them.
+fn object_keys<'a>(
+    ty: &'a Type,
+    scope: &Scope<'a>,
+    visited: &mut HashSet<&'a str>,
+) -> HashSet<&'a str> {
+    match &ty.kind {
+        _ if ty.meta.nullable => HashSet::new(),
+        Kind::Object(fields, _) => fields.keys().map(String::as_str).collect(),
+        Kind::Reference(name) if visited.insert(name) => scope
+            .definitions
+            .get(name.as_str())
+            .map(|d| object_keys(&d.schema, scope, visited))
+            .unwrap_or_default(),
+        Kind::Intersection(parts) => parts
+            .iter()
+            .flat_map(|p| object_keys(p, scope, visited))
+            .collect(),
+        Kind::Tagged {
+            variant, property, ..
+        } => {
+            let mut keys = object_keys(variant, scope, visited);
+            keys.insert(property.as_str());
+            keys
+        }
+        _ => HashSet::new(),
+    }
+}
+
+/// The discriminator of a tagged union with the values identifying each variant and what the variant carries,
+/// if every variant narrows the same property of a reference or an object to strings.
+fn tagged_variants(ty: &Type) -> Option<(&str, Vec<TaggedVariant<'_>>)> {
+    let variants = match &ty.kind {
+        Kind::Union(variants) if !ty.meta.nullable => variants,
+        _ => return None,
+    };
+    let mut tag = None;
+    let mut tagged = vec![];
+    for variant in variants {
+        let (payload, key, values) = match &variant.kind {
+            Kind::Tagged {
+                variant: payload,
+                property,
+                values,
+            } if !variant.meta.nullable => (payload.as_ref(), property, values),
+            _ => return None,
+        };
+        let values = values
+            .iter()
+            .map(|value| match value {
+                JavaScriptValue::String(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let payload_is_object = !payload.meta.nullable
+            && match &payload.kind {
+                Kind::Reference(_) => true,
+                Kind::Object(fields, _) => !fields.is_empty(),
+                _ => false,
+            };
+        if values.is_empty() || !payload_is_object || *tag.get_or_insert(key.as_str()) != key {
+            return None;
+        }
+        tagged.push((values, payload));
+    }
+    Some((tag?, tagged))
+}
+
+/// A PascalCase variant name for arbitrary text.
+fn variant_name(s: &str) -> String {
+    match naming::identifier(s) {
+        name if name == "Self" => "Self_".to_string(),
+        name => name,
+    }
+}
+
+/// The name of a variant of an untagged union, after what it carries.
+fn untagged_variant_name(ty: &Type) -> String {
+    match &ty.kind {
+        _ if ty.meta.nullable => "Value".to_string(),
+        Kind::Reference(t) | Kind::Mapped(t) | Kind::Other(t) => variant_name(t),
+        Kind::Any => "Any".to_string(),
+        Kind::Never => "Never".to_string(),
+        Kind::Unknown => "Unknown".to_string(),
+        Kind::Boolean => "Boolean".to_string(),
+        Kind::Integer | Kind::Number => "Number".to_string(),
+        Kind::String => "String".to_string(),
+        Kind::Date => "Date".to_string(),
+        Kind::Array(t) => format!("{}List", untagged_variant_name(t)),
+        Kind::Tuple(..) => "Tuple".to_string(),
+        Kind::Object(..) => "Object".to_string(),
+        Kind::Null => "Null".to_string(),
+        Kind::Const(v) => match v {
+            JavaScriptValue::Null => "Null",
+            JavaScriptValue::String(_) => "String",
+            JavaScriptValue::Number(_) => "Number",
+            JavaScriptValue::Boolean(_) => "Boolean",
+            JavaScriptValue::Array(_) | JavaScriptValue::Object(_) => "Value",
+        }
+        .to_string(),
+        _ => "Value".to_string(),
+    }
+}
+
+/// The doc comment of a type or a field, empty if there is nothing to document.
+fn print_docs(docs: &Documentation, level: usize) -> String {
+    let text = |t: &String| {
+        t.trim_end()
+            .lines()
+            .map(|l| l.trim_end().to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut lines = docs.title.iter().flat_map(text).collect::<Vec<_>>();
+    if docs.title.is_some() && docs.description.is_some() {
+        lines.push(String::new());
+    }
+    lines.extend(docs.description.iter().flat_map(text));
+    let indent = INDENT.repeat(level);
+    lines
+        .iter()
+        .map(|l| {
+            if l.is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, l)
+            }
+        })
+        .collect()
+}
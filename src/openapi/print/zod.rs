@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+
+use super::Printer;
+use crate::ir::{self, dependency_order, is_recursive};
+use crate::repr::{brands, AdditionalProperties, Definition, JavaScriptType, JavaScriptValue};
+
+/// What the schema of a definition may refer to.
+struct Scope<'a> {
+    /// Every definition.
+    definitions: &'a HashSet<String>,
+    /// The definitions that aren't declared yet, which are referred to through `z.lazy`.
+    lazy: &'a HashSet<String>,
+}
+
+impl<'a> Printer<'a> {
+    /// A [zod](https://zod.dev) schema for every definition, declared after the ones it refers to,
+    /// with its type inferred from it.
+    /// The type of a recursive definition can't be inferred, so it is declared as usual and annotates the schema.
+    pub fn print_zod(&self, definitions: &[ir::Definition]) -> String {
+        let lowered = definitions.iter().map(Definition::from).collect::<Vec<_>>();
+        let by_name = lowered
+            .iter()
+            .map(|d| (d.name.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        let names = lowered.iter().map(|d| d.name.clone()).collect();
+        let mut declared = HashSet::new();
+        let mut code = vec![format!(
+            "import {{ z }} from {}{}\n",
+            self.print_string("zod"),
+            self.terminator()
+        )];
+        // The schemas brand their values themselves, so only the types are declared.
+        code.extend(brands(&lowered).into_iter().map(|(brand, jtype)| {
+            format!(
+                "export type {} = {} & z.BRAND<{}>{}",
+                brand,
+                self.print_intersected(jtype, 0),
+                self.print_string(brand),
+                self.terminator()
+            )
+        }));
+        for group in dependency_order(definitions) {
+            let recursive = is_recursive(&group);
+            for definition in group.iter().map(|d| by_name[d.name.as_str()]) {
+                let lazy = group
+                    .iter()
+                    .map(|d| d.name.clone())
+                    .filter(|name| !declared.contains(name))
+                    .collect::<HashSet<_>>();
+                let scope = Scope {
+                    definitions: &names,
+                    lazy: &lazy,
+                };
+                let schema = self.print_schema(&definition.jtype, 0, &scope);
+                code.push(if recursive {
+                    format!(
+                        "{}\nexport const {}: z.ZodType<{}> = {}{}",
+                        self.print_definition(definition),
+                        definition.name,
+                        definition.name,
+                        schema,
+                        self.terminator()
+                    )
+                } else {
+                    format!(
+                        "{}export const {} = {}{}\nexport type {} = z.infer<typeof {}>{}",
+                        self.print_docs(&definition.docs, 0),
+                        definition.name,
+                        schema,
+                        self.terminator(),
+                        definition.name,
+                        definition.name,
+                        self.terminator()
+                    )
+                });
+                declared.insert(definition.name.clone());
+            }
+        }
+        code.join("\n")
+    }
+
+    /// The schema of the type, where `level` is the indentation of the line it starts on.
+    fn print_schema(&self, jtype: &JavaScriptType, level: usize, scope: &Scope) -> String {
+        match jtype {
+            JavaScriptType::Typename(t) => match t.as_str() {
+                "any" | "boolean" | "never" | "number" | "string" | "undefined" | "unknown" => {
+                    format!("z.{}()", t)
+                }
+                // Dates are transmitted as strings.
+                "Date" => "z.coerce.date()".to_string(),
+                "bigint" => "z.coerce.bigint()".to_string(),
+                "Blob" => "z.instanceof(Blob)".to_string(),
+                name if scope.lazy.contains(name) => format!("z.lazy(() => {})", name),
+                name if scope.definitions.contains(name) => name.to_string(),
+                _ => "z.unknown()".to_string(),
+            },
+            JavaScriptType::Array(t) => self.print_zod_call(
+                "z.array",
+                |level| vec![self.print_schema(t, level, scope)],
+                level,
+            ),
+            JavaScriptType::Tuple(elements, rest) => {
+                let tuple = self.print_zod_call(
+                    "z.tuple",
+                    |level| {
+                        vec![self.print_zod_list(
+                            |level| {
+                                elements
+                                    .iter()
+                                    .map(|t| self.print_schema(t, level, scope))
+                                    .collect()
+                            },
+                            level,
+                        )]
+                    },
+                    level,
+                );
+                match rest {
+                    Some(rest) => {
+                        format!("{}.rest({})", tuple, self.print_schema(rest, level, scope))
+                    }
+                    None => tuple,
+                }
+            }
+            JavaScriptType::Product(parts) => self.print_intersection(parts, level, scope),
+            JavaScriptType::Sum(variants) => match jtype.string_enum_values() {
+                Some(values) => self.print_zod_call(
+                    "z.enum",
+                    |level| {
+                        vec![self.print_zod_list(
+                            |_| values.iter().map(|v| self.print_string(v)).collect(),
+                            level,
+                        )]
+                    },
+                    level,
+                ),
+                None => self.print_zod_call(
+                    "z.union",
+                    |level| {
+                        vec![self.print_zod_list(
+                            |level| {
+                                variants
+                                    .iter()
+                                    .map(|t| self.print_schema(t, level, scope))
+                                    .collect()
+                            },
+                            level,
+                        )]
+                    },
+                    level,
+                ),
+            },
+            JavaScriptType::Nullable(t) => {
+                format!("{}.nullable()", self.print_schema(t, level, scope))
+            }
+            JavaScriptType::Value(v) => match v.as_ref() {
+                JavaScriptValue::Null => "z.null()".to_string(),
+                JavaScriptValue::Array(_) | JavaScriptValue::Object(_) => "z.unknown()".to_string(),
+                v => format!("z.literal({})", self.print_value(v)),
+            },
+            // zod can't negate a schema either.
+            JavaScriptType::Not(_) => "z.unknown()".to_string(),
+            JavaScriptType::Branded(brand, t) => format!(
+                "{}.brand<{}>()",
+                self.print_schema(t, level, scope),
+                self.print_string(brand)
+            ),
+            JavaScriptType::AnonymousObject(rows, AdditionalProperties::Allowed(t))
+                if rows.is_empty() =>
+            {
+                self.print_zod_call(
+                    "z.record",
+                    |level| vec![self.print_schema(t, level, scope)],
+                    level,
+                )
+            }
+            JavaScriptType::AnonymousObject(rows, additional) => {
+                let object = if rows.is_empty() {
+                    "z.object({})".to_string()
+                } else {
+                    format!(
+                        "z.object({{\n{}{}}})",
+                        rows.iter()
+                            .map(|(k, p)| {
+                                let schema = self.print_schema(&p.jtype, level + 1, scope);
+                                format!(
+                                    "{}{}{}: {}{},\n",
+                                    self.print_docs(&p.docs, level + 1),
+                                    self.indent(level + 1),
+                                    self.print_key(k),
+                                    schema,
+                                    if p.required { "" } else { ".optional()" }
+                                )
+                            })
+                            .collect::<String>(),
+                        self.indent(level)
+                    )
+                };
+                match additional {
+                    // Unknown properties are stripped.
+                    AdditionalProperties::Unspecified => object,
+                    AdditionalProperties::Forbidden => format!("{}.strict()", object),
+                    AdditionalProperties::Allowed(t) => {
+                        format!(
+                            "{}.catchall({})",
+                            object,
+                            self.print_schema(t, level, scope)
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    /// `z.intersection` only takes two schemas, so the intersection of more is nested on the right.
+    fn print_intersection(&self, parts: &[JavaScriptType], level: usize, scope: &Scope) -> String {
+        match parts {
+            [] => "z.unknown()".to_string(),
+            [part] => self.print_schema(part, level, scope),
+            [first, rest @ ..] => self.print_zod_call(
+                "z.intersection",
+                |level| {
+                    vec![
+                        self.print_schema(first, level, scope),
+                        self.print_intersection(rest, level, scope),
+                    ]
+                },
+                level,
+            ),
+        }
+    }
+
+    /// A call with its arguments on the same line if they fit or if there is only one of them,
+    /// with one argument per line otherwise.
+    /// The arguments are printed by `arguments` given the indentation of the line they start on.
+    fn print_zod_call<F>(&self, callee: &str, arguments: F, level: usize) -> String
+    where
+        F: Fn(usize) -> Vec<String>,
+    {
+        let inline = arguments(level);
+        let call = format!("{}({})", callee, inline.join(", "));
+        if inline.len() == 1
+            || (!call.contains('\n')
+                && self.indent(level).chars().count() + call.chars().count()
+                    <= self.format.line_width)
+        {
+            call
+        } else {
+            format!(
+                "{}(\n{}{})",
+                callee,
+                arguments(level + 1)
+                    .iter()
+                    .map(|a| format!("{}{},\n", self.indent(level + 1), a))
+                    .collect::<String>(),
+                self.indent(level)
+            )
+        }
+    }
+
+    /// An array literal on a single line if it fits, with one element per line otherwise.
+    fn print_zod_list<F>(&self, elements: F, level: usize) -> String
+    where
+        F: Fn(usize) -> Vec<String>,
+    {
+        let inline = format!("[{}]", elements(level).join(", "));
+        if !inline.contains('\n')
+            && self.indent(level).chars().count() + inline.chars().count() <= self.format.line_width
+        {
+            inline
+        } else {
+            format!(
+                "[\n{}{}]",
+                elements(level + 1)
+                    .iter()
+                    .map(|e| format!("{}{},\n", self.indent(level + 1), e))
+                    .collect::<String>(),
+                self.indent(level)
+            )
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, ops::Not};
+use indexmap::IndexMap;
+
+use crate::ir::{self, Additional, Kind};
+use crate::print::{Format, Printer};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
 pub enum JavaScriptValue {
@@ -7,7 +11,7 @@ pub enum JavaScriptValue {
     Boolean(bool),
     Number(f64),
     Array(Vec<JavaScriptValue>),
-    Object(HashMap<String, JavaScriptValue>),
+    Object(IndexMap<String, JavaScriptValue>),
 }
 
 impl PartialEq for JavaScriptValue {
@@ -18,10 +22,9 @@ impl PartialEq for JavaScriptValue {
             (JavaScriptValue::Null, JavaScriptValue::Null) => true,
             (JavaScriptValue::Number(l), JavaScriptValue::Number(r)) => l.eq(r),
             (JavaScriptValue::String(l), JavaScriptValue::String(r)) => l.eq(r),
-            (JavaScriptValue::Object(l), JavaScriptValue::Object(r)) => l
-                .iter()
-                .zip(r.iter())
-                .all(|((l1, l2), (r1, r2))| l1.eq(r1) && l2.eq(r2)),
+            (JavaScriptValue::Object(l), JavaScriptValue::Object(r)) => {
+                l.len() == r.len() && l.iter().all(|(k, v)| r.get(k) == Some(v))
+            }
             _ => false,
         }
     }
@@ -59,57 +62,206 @@ impl From<&f64> for JavaScriptValue {
     }
 }
 
-impl From<&JavaScriptValue> for String {
-    fn from(v: &JavaScriptValue) -> Self {
+const INVALID_KEY_TYPE_ERROR : &str = "Although YAML technically support having non-string keys. Only strings are valid keys in a JavaScript object.";
+
+impl From<&serde_yaml::Value> for JavaScriptValue {
+    fn from(v: &serde_yaml::Value) -> Self {
         match v {
-            JavaScriptValue::Null => "null".to_string(),
-            JavaScriptValue::String(s) => {
-                format!("'{}'", s)
-            }
-            JavaScriptValue::Boolean(b) => if *b { "true" } else { "false" }.to_string(),
-            JavaScriptValue::Number(n) => {
-                format!("{}", n)
-            }
-            JavaScriptValue::Array(v) => {
-                format!(
-                    "[{}]",
-                    v.iter()
-                        .map(String::from)
-                        .collect::<Vec<String>>()
-                        .join(",")
-                )
-            }
-            JavaScriptValue::Object(o) => {
-                format!(
-                    "{{{}}}",
-                    o.iter()
-                        .map(|(name, value)| format!(
-                            "\"{}\":{}",
-                            String::from(name),
-                            String::from(value)
-                        ))
-                        .collect::<Vec<String>>()
-                        .join(",")
-                )
+            serde_yaml::Value::Null => JavaScriptValue::Null,
+            serde_yaml::Value::Bool(b) => JavaScriptValue::from(b),
+            serde_yaml::Value::Number(n) => JavaScriptValue::from(n.as_f64().unwrap()),
+            serde_yaml::Value::String(s) => JavaScriptValue::from(s.as_str()),
+            serde_yaml::Value::Sequence(v) => {
+                JavaScriptValue::Array(v.iter().map(JavaScriptValue::from).collect())
             }
+            serde_yaml::Value::Mapping(o) => JavaScriptValue::Object(
+                o.iter()
+                    .filter_map(|(k, v)| match k {
+                        serde_yaml::Value::String(s) => Some((s, v)),
+                        _ => {
+                            eprintln!("error:\n{}value:\n{:#?}", INVALID_KEY_TYPE_ERROR, k);
+                            None
+                        }
+                    })
+                    .map(|(name, value)| (name.clone(), JavaScriptValue::from(value)))
+                    .collect(),
+            ),
         }
     }
 }
 
+/// What the specification says about a type or a property, rendered as a JSDoc block.
+#[derive(Debug, Clone, Default)]
+pub struct Documentation {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub deprecated: bool,
+    pub default: Option<JavaScriptValue>,
+    pub examples: Vec<JavaScriptValue>,
+    pub format: Option<String>,
+    /// Validation keywords TypeScript can't express, e.g. `("minLength", "1")`.
+    pub constraints: Vec<(&'static str, String)>,
+    /// The names of the values of an enum, from `x-enum-varnames` or `x-enumNames`.
+    pub enum_names: Vec<String>,
+}
+
+/// A named property of an object.
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub required: bool,
+    pub jtype: JavaScriptType,
+    pub docs: Documentation,
+}
+
+/// A named type of the specification.
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub name: String,
+    pub jtype: JavaScriptType,
+    pub docs: Documentation,
+}
+
+/// What an object accepts besides its named properties.
+#[derive(Debug, Clone)]
+pub enum AdditionalProperties {
+    /// The schema does not say, which means anything is accepted.
+    Unspecified,
+    /// `additionalProperties: false`.
+    Forbidden,
+    /// `additionalProperties: true` or a schema.
+    Allowed(Box<JavaScriptType>),
+}
+
 #[derive(Debug, Clone)]
 pub enum JavaScriptType {
     Array(Box<JavaScriptType>),
+    /// A fixed list of elements, optionally followed by any number of elements of another type.
+    Tuple(Vec<JavaScriptType>, Option<Box<JavaScriptType>>),
     Product(Vec<JavaScriptType>),
     Sum(Vec<JavaScriptType>),
     Typename(String),
-    AnonymousObject(HashMap<String, (bool, JavaScriptType)>),
+    /// An object with named properties, rendered as `Record<string, T>` if it only has additional properties.
+    AnonymousObject(IndexMap<String, Property>, AdditionalProperties),
     Value(Box<JavaScriptValue>),
+    /// The inner type or `null`, regardless of whether the property holding it is required.
+    Nullable(Box<JavaScriptType>),
+    /// Any value except the ones of the inner type.
+    /// TypeScript has no negated types so this is rendered as `unknown` with a comment.
+    Not(Box<JavaScriptType>),
+    /// A nominal type accepting the values of the inner type, rendered as its name.
+    /// Its declaration, found by [`brands`], is `T & { readonly __brand: "Name" }`.
+    Branded(String, Box<JavaScriptType>),
 }
 
 impl JavaScriptType {
     pub fn typename<T: Into<String>>(str: T) -> JavaScriptType {
         JavaScriptType::Typename(str.into())
     }
+
+    /// Narrows the `property` of `variant` to one of `values`, making it a variant of a tagged union.
+    pub fn tagged(
+        variant: JavaScriptType,
+        property: &str,
+        values: Vec<JavaScriptValue>,
+    ) -> JavaScriptType {
+        let mut rows = IndexMap::new();
+        let tag = JavaScriptType::sum(
+            values
+                .into_iter()
+                .map(|v| JavaScriptType::Value(Box::new(v)))
+                .collect(),
+        );
+        rows.insert(
+            property.to_string(),
+            Property {
+                required: true,
+                jtype: tag,
+                docs: Documentation::default(),
+            },
+        );
+        let tag = JavaScriptType::AnonymousObject(rows, AdditionalProperties::Unspecified);
+        match variant {
+            JavaScriptType::Product(mut parts) => {
+                parts.push(tag);
+                JavaScriptType::Product(parts)
+            }
+            variant => JavaScriptType::Product(vec![variant, tag]),
+        }
+    }
+
+    /// The values of a string enum, a union of string literals.
+    pub fn string_enum_values(&self) -> Option<Vec<&str>> {
+        match self {
+            JavaScriptType::Sum(variants) => variants
+                .iter()
+                .map(|v| match v {
+                    JavaScriptType::Value(v) => match v.as_ref() {
+                        JavaScriptValue::String(s) => Some(s.as_str()),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// A union of `variants` where a `null` variant makes the rest [`JavaScriptType::Nullable`].
+    pub fn sum(variants: Vec<JavaScriptType>) -> JavaScriptType {
+        let (nulls, mut rest): (Vec<_>, Vec<_>) = variants
+            .into_iter()
+            .partition(|v| matches!(v, JavaScriptType::Value(v) if **v == JavaScriptValue::Null));
+        let sum = match rest.len() {
+            0 => return JavaScriptType::Value(Box::new(JavaScriptValue::Null)),
+            1 => rest.remove(0),
+            _ => JavaScriptType::Sum(rest),
+        };
+        if nulls.is_empty() {
+            sum
+        } else {
+            JavaScriptType::Nullable(Box::new(sum))
+        }
+    }
+
+    /// The parts of an intersection, including the ones of the intersections nested in it,
+    /// or the type itself if it isn't one.
+    pub fn intersected(&self) -> Vec<&JavaScriptType> {
+        match self {
+            JavaScriptType::Product(parts) => {
+                parts.iter().flat_map(JavaScriptType::intersected).collect()
+            }
+            jtype => vec![jtype],
+        }
+    }
+
+    /// The names of the types referred to, in order of appearance and with duplicates.
+    pub fn typenames(&self) -> Vec<&str> {
+        match self {
+            JavaScriptType::Typename(t) => vec![t.as_str()],
+            JavaScriptType::Array(t)
+            | JavaScriptType::Nullable(t)
+            | JavaScriptType::Not(t)
+            | JavaScriptType::Branded(_, t) => t.typenames(),
+            JavaScriptType::Tuple(elements, rest) => elements
+                .iter()
+                .chain(rest.iter().map(Box::as_ref))
+                .flat_map(JavaScriptType::typenames)
+                .collect(),
+            JavaScriptType::Product(parts) | JavaScriptType::Sum(parts) => {
+                parts.iter().flat_map(JavaScriptType::typenames).collect()
+            }
+            JavaScriptType::AnonymousObject(rows, additional) => rows
+                .values()
+                .map(|p| &p.jtype)
+                .chain(match additional {
+                    AdditionalProperties::Allowed(t) => Some(t.as_ref()),
+                    _ => None,
+                })
+                .flat_map(JavaScriptType::typenames)
+                .collect(),
+            JavaScriptType::Value(_) => vec![],
+        }
+    }
 }
 
 impl std::fmt::Display for JavaScriptType {
@@ -117,131 +269,132 @@ impl std::fmt::Display for JavaScriptType {
         write!(
             f,
             "{}",
-            match self {
-                JavaScriptType::Array(o) => {
-                    format!("({})[]", o)
-                }
-                JavaScriptType::AnonymousObject(o) => {
-                    format!(
-                        "{{\n{}\n}}",
-                        o.iter()
-                            .map(|(k, v)| {
-                                format!("\t'{}' {} : {};", k, if v.0 { "" } else { "?" }, v.1)
-                            })
-                            .collect::<Vec<String>>()
-                            .join("\n")
-                    )
-                }
-                JavaScriptType::Product(p) => {
-                    p.iter()
-                        .map(|x| format!("{}", x))
-                        .collect::<Vec<String>>()
-                        .join("&")
-                }
-                JavaScriptType::Sum(s) => {
-                    s.iter()
-                        .map(|s| format!("{}", s))
-                        .collect::<Vec<String>>()
-                        .join("|")
-                }
-                JavaScriptType::Typename(o) => o.to_string(),
-                JavaScriptType::Value(v) => {
-                    String::from(v.as_ref())
-                }
-            }
+            Printer::new(&Format::default()).print_type(self, 0)
         )
     }
 }
 
-pub fn filter_empty_types(tt: &JavaScriptType) -> Option<JavaScriptType> {
-    match tt {
-        JavaScriptType::Array(t) => {
-            filter_empty_types(&*t).map(|t| JavaScriptType::Array(Box::new(t)))
-        }
-        JavaScriptType::Product(p) => {
-            let result = p
-                .iter()
-                .filter_map(|v| filter_empty_types(v))
-                .collect::<Vec<JavaScriptType>>();
-            if result.is_empty() {
-                None
-            } else {
-                Some(JavaScriptType::Product(result))
+/// The TypeScript rendering of the schema.
+impl From<&ir::Type> for JavaScriptType {
+    fn from(ty: &ir::Type) -> Self {
+        let jtype = match &ty.kind {
+            Kind::Any => JavaScriptType::typename("any"),
+            Kind::Never => JavaScriptType::typename("never"),
+            Kind::Unknown => JavaScriptType::typename("unknown"),
+            Kind::Null => JavaScriptType::Value(Box::new(JavaScriptValue::Null)),
+            Kind::Boolean => JavaScriptType::typename("boolean"),
+            Kind::Integer | Kind::Number => JavaScriptType::typename("number"),
+            Kind::String => JavaScriptType::typename("string"),
+            Kind::Date => JavaScriptType::typename("Date"),
+            Kind::Mapped(target) => JavaScriptType::Typename(target.clone()),
+            Kind::Branded(brand, t) => {
+                JavaScriptType::Branded(brand.clone(), Box::new(t.as_ref().into()))
             }
-        }
-        JavaScriptType::Sum(s) => {
-            let result = s
-                .iter()
-                .filter_map(|v| filter_empty_types(v))
-                .collect::<Vec<JavaScriptType>>();
-            if result.is_empty() {
-                None
-            } else {
-                Some(JavaScriptType::Sum(result))
+            Kind::Const(value) => JavaScriptType::Value(Box::new(value.clone())),
+            Kind::Enum(values) => JavaScriptType::Sum(
+                values
+                    .iter()
+                    .map(|v| JavaScriptType::Value(Box::new(v.clone())))
+                    .collect(),
+            ),
+            Kind::Array(items) => JavaScriptType::Array(Box::new(items.as_ref().into())),
+            Kind::Tuple(elements, rest) => JavaScriptType::Tuple(
+                elements.iter().map(JavaScriptType::from).collect(),
+                rest.as_ref().map(|t| Box::new(t.as_ref().into())),
+            ),
+            Kind::Object(fields, additional) => JavaScriptType::AnonymousObject(
+                fields
+                    .iter()
+                    .map(|(k, f)| (k.clone(), Property::from(f)))
+                    .collect(),
+                match additional {
+                    Additional::Unspecified => AdditionalProperties::Unspecified,
+                    Additional::Forbidden => AdditionalProperties::Forbidden,
+                    Additional::Allowed(t) => {
+                        AdditionalProperties::Allowed(Box::new(t.as_ref().into()))
+                    }
+                },
+            ),
+            Kind::Reference(name) | Kind::Other(name) => JavaScriptType::Typename(name.clone()),
+            Kind::Union(variants) => {
+                JavaScriptType::sum(variants.iter().map(JavaScriptType::from).collect())
             }
-        }
-        JavaScriptType::AnonymousObject(o) => {
-            let result = o
-                .iter()
-                .filter_map(|(k, v)| filter_empty_types(&v.1).map(|tt| (k.clone(), (v.0, tt))))
-                .collect::<HashMap<_, _>>();
-            if result.is_empty() {
-                None
-            } else {
-                Some(JavaScriptType::AnonymousObject(result))
+            Kind::Intersection(parts) => {
+                JavaScriptType::Product(parts.iter().map(JavaScriptType::from).collect())
             }
+            Kind::Not(t) => JavaScriptType::Not(Box::new(t.as_ref().into())),
+            Kind::Tagged {
+                variant,
+                property,
+                values,
+            } => JavaScriptType::tagged(variant.as_ref().into(), property, values.clone()),
+        };
+        if ty.meta.nullable {
+            JavaScriptType::sum(vec![
+                jtype,
+                JavaScriptType::Value(Box::new(JavaScriptValue::Null)),
+            ])
+        } else {
+            jtype
         }
-        JavaScriptType::Value(v) => Some(JavaScriptType::Value(v.clone())),
-        JavaScriptType::Typename(t) => Some(JavaScriptType::Typename(t.clone())),
     }
 }
 
-pub fn filter_unwanted_types(tt: &JavaScriptType, skip_types: &[&str]) -> Option<JavaScriptType> {
-    match tt {
-        JavaScriptType::Array(t) => {
-            filter_unwanted_types(&*t, skip_types).map(|t| JavaScriptType::Array(Box::new(t)))
+impl From<&ir::Field> for Property {
+    fn from(field: &ir::Field) -> Self {
+        Property {
+            required: field.required,
+            jtype: JavaScriptType::from(&field.schema),
+            docs: field.schema.meta.docs.clone(),
         }
-        JavaScriptType::Product(p) => {
-            let result = p
-                .iter()
-                .cloned()
-                .filter_map(|v| filter_unwanted_types(&v, skip_types))
-                .collect::<Vec<JavaScriptType>>();
-            if result.is_empty() {
-                None
-            } else {
-                Some(JavaScriptType::Product(result))
-            }
+    }
+}
+
+impl From<&ir::Definition> for Definition {
+    fn from(definition: &ir::Definition) -> Self {
+        Definition {
+            name: definition.name.clone(),
+            jtype: JavaScriptType::from(&definition.schema),
+            docs: definition.schema.meta.docs.clone(),
         }
-        JavaScriptType::Sum(s) => {
-            let result = s
-                .iter()
-                .cloned()
-                .filter_map(|v| filter_unwanted_types(&v, skip_types))
-                .collect::<Vec<JavaScriptType>>();
-            if result.is_empty() {
-                None
-            } else {
-                Some(JavaScriptType::Sum(result))
+    }
+}
+
+/// The brands the definitions refer to, by name, with the type of their values where they first appear.
+/// A brand named after a definition is declared by it rather than on its own, so it is left out.
+pub fn brands(definitions: &[Definition]) -> BTreeMap<&str, &JavaScriptType> {
+    fn visit<'a>(tt: &'a JavaScriptType, brands: &mut BTreeMap<&'a str, &'a JavaScriptType>) {
+        match tt {
+            JavaScriptType::Array(t) | JavaScriptType::Nullable(t) | JavaScriptType::Not(t) => {
+                visit(t, brands)
             }
-        }
-        JavaScriptType::AnonymousObject(o) => {
-            let result = o
+            JavaScriptType::Tuple(elements, rest) => elements
                 .iter()
-                .filter_map(|(k, v)| {
-                    filter_unwanted_types(&v.1, skip_types).map(|tt| (k.clone(), (v.0, tt)))
-                })
-                .collect::<HashMap<_, _>>();
-            if result.is_empty() {
-                None
-            } else {
-                Some(JavaScriptType::AnonymousObject(result))
+                .chain(rest.iter().map(Box::as_ref))
+                .for_each(|t| visit(t, brands)),
+            JavaScriptType::Product(parts) | JavaScriptType::Sum(parts) => {
+                parts.iter().for_each(|t| visit(t, brands))
+            }
+            JavaScriptType::AnonymousObject(rows, additional) => {
+                rows.values().for_each(|p| visit(&p.jtype, brands));
+                if let AdditionalProperties::Allowed(t) = additional {
+                    visit(t, brands)
+                }
             }
+            JavaScriptType::Branded(brand, t) => {
+                brands.entry(brand.as_str()).or_insert(t);
+                visit(t, brands)
+            }
+            JavaScriptType::Typename(_) | JavaScriptType::Value(_) => (),
         }
-        JavaScriptType::Value(v) => Some(JavaScriptType::Value(v.clone())),
-        JavaScriptType::Typename(t) => skip_types
-            .contains(&t.as_str())
-            .not()
-            .then(|| JavaScriptType::Typename(t.clone())),
     }
+
+    let mut brands = BTreeMap::new();
+    for definition in definitions {
+        visit(&definition.jtype, &mut brands);
+    }
+    for definition in definitions {
+        brands.remove(definition.name.as_str());
+    }
+    brands
 }
@@ -0,0 +1,500 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+
+use crate::error::Error;
+use crate::pointer;
+
+/// Keywords of a schema holding schemas by name.
+const SCHEMA_MAPS: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "definitions",
+    "$defs",
+    "dependentSchemas",
+];
+
+/// Keywords of a schema holding a schema or a list of schemas.
+const SUBSCHEMAS: &[&str] = &[
+    "items",
+    "prefixItems",
+    "additionalItems",
+    "additionalProperties",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "propertyNames",
+    "contains",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "contentSchema",
+];
+
+/// Keys of the other objects holding objects by name (path items, responses, parameters...).
+const OBJECT_MAPS: &[&str] = &[
+    "paths",
+    "webhooks",
+    "pathItems",
+    "callbacks",
+    "responses",
+    "parameters",
+    "requestBodies",
+    "headers",
+    "links",
+    "securitySchemes",
+    "content",
+    "encoding",
+];
+
+/// Keys holding literal data that may legitimately contain a `$ref` key.
+const LITERAL_KEYS: &[&str] = &["example", "examples", "default", "enum", "const"];
+
+/// Segments that only describe the structure of a schema and make for poor type names.
+const STRUCTURAL_SEGMENTS: &[&str] = &[
+    "definitions",
+    "components",
+    "schemas",
+    "$defs",
+    "properties",
+    "items",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "additionalProperties",
+    "prefixItems",
+    "patternProperties",
+    "content",
+    "schema",
+];
+
+/// A document (`None` if it is an anonymous root document) and a JSON pointer inside of it.
+type Location = (Option<PathBuf>, String);
+
+/// What a value of the document is, known from where it is rather than from the name of its key,
+/// since a property can be named after any keyword.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Node {
+    /// A schema, whose references are imported as named types.
+    Schema,
+    /// Schemas by name, e.g. `properties` or `components.schemas`.
+    Schemas,
+    /// Anything else, e.g. an operation, a parameter or a response, whose references are inlined.
+    Object,
+    /// Objects by name, e.g. `paths` or `responses`.
+    Objects,
+}
+
+impl Node {
+    /// What the value of `key` is, `None` if it is literal data which may contain a `$ref` key.
+    fn child(self, key: &str) -> Option<Node> {
+        match self {
+            Node::Schemas => Some(Node::Schema),
+            _ if key.starts_with("x-") => None,
+            // A response can be called `default`.
+            Node::Objects => Some(Node::Object),
+            _ if LITERAL_KEYS.contains(&key) => None,
+            Node::Schema if SCHEMA_MAPS.contains(&key) => Some(Node::Schemas),
+            Node::Schema if SUBSCHEMAS.contains(&key) => Some(Node::Schema),
+            // The `items` of a Swagger 2.0 parameter or header are a schema too.
+            Node::Object if key == "schema" || key == "items" => Some(Node::Schema),
+            Node::Object if key == "schemas" || key == "definitions" => Some(Node::Schemas),
+            Node::Object if OBJECT_MAPS.contains(&key) => Some(Node::Objects),
+            _ => Some(Node::Object),
+        }
+    }
+
+    /// What the elements of a list are, e.g. the schemas of `allOf` or the parameters of an operation.
+    fn element(self) -> Node {
+        match self {
+            Node::Schema | Node::Schemas => Node::Schema,
+            Node::Object | Node::Objects => Node::Object,
+        }
+    }
+}
+
+/// Bundles a document and everything it references into a single self-contained document.
+///
+/// Every `$ref` to a schema that is not a direct child of the definitions of the root document
+/// (a schema in another file, or a nested fragment like `#/definitions/Pet/properties/tag`)
+/// is imported into the definitions under a new, unique name and the reference is rewritten to point to it.
+/// References to anything else (parameters, responses...) are replaced with their target.
+/// Relative files are loaded relative to the document referencing them and each of them is only read once.
+pub struct Resolver {
+    root: Option<PathBuf>,
+    definitions: &'static [&'static str],
+    documents: HashMap<Option<PathBuf>, Value>,
+    imports: HashMap<Location, String>,
+    imported: Vec<(String, Value)>,
+    names: HashSet<String>,
+    inlining: Vec<Location>,
+}
+
+/// Resolves all references of `document`, which was read from `path` if it is known.
+/// Without a path, only references inside of `document` itself can be resolved.
+pub fn resolve(document: Value, path: Option<&Path>) -> Result<Value, Error> {
+    let root = match path {
+        Some(path) => Some(path.canonicalize()?),
+        None => None,
+    };
+    let definitions: &'static [&'static str] = if document.get("swagger").is_some() {
+        &["definitions"]
+    } else {
+        &["components", "schemas"]
+    };
+    let names = definitions
+        .iter()
+        .try_fold(&document, |value, key| value.get(*key))
+        .and_then(Value::as_mapping)
+        .map(|m| {
+            m.iter()
+                .filter_map(|(k, _)| k.as_str())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut resolver = Resolver {
+        root: root.clone(),
+        definitions,
+        documents: HashMap::new(),
+        imports: HashMap::new(),
+        imported: vec![],
+        names,
+        inlining: vec![],
+    };
+    resolver.documents.insert(root.clone(), document.clone());
+    let mut document = document;
+    resolver.walk(&mut document, &root, String::from("#"), Node::Object)?;
+    resolver.insert_imports(&mut document);
+    Ok(document)
+}
+
+impl Resolver {
+    fn walk(
+        &mut self,
+        value: &mut Value,
+        document: &Option<PathBuf>,
+        pointer: String,
+        node: Node,
+    ) -> Result<(), Error> {
+        match value {
+            Value::Mapping(mapping) => {
+                let reference = match node {
+                    Node::Schema | Node::Object => {
+                        mapping.get(&Value::from("$ref")).and_then(Value::as_str)
+                    }
+                    Node::Schemas | Node::Objects => None,
+                };
+                if let Some(reference) = reference {
+                    let reference = reference.to_string();
+                    if node == Node::Schema {
+                        let reference = self.import(&reference, document, &pointer)?;
+                        mapping.insert(Value::from("$ref"), Value::from(reference));
+                    } else {
+                        *value = self.inline(&reference, document, &pointer, node)?;
+                        return Ok(());
+                    }
+                }
+                for (k, v) in mapping.iter_mut() {
+                    let k = match k.as_str() {
+                        Some("$ref") if node != Node::Schemas => continue,
+                        Some(k) => k,
+                        None => continue,
+                    };
+                    if k == "$defs" && node == Node::Schema {
+                        self.import_all(v, document, &pointer::join(&pointer, k))?;
+                    } else if let Some(child) = node.child(k) {
+                        self.walk(v, document, pointer::join(&pointer, k), child)?;
+                    }
+                }
+                Ok(())
+            }
+            Value::Sequence(sequence) => {
+                for (idx, v) in sequence.iter_mut().enumerate() {
+                    let pointer = pointer::join(&pointer, idx.to_string());
+                    self.walk(v, document, pointer, node.element())?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Imports the schema behind `reference` and returns the local reference to use instead.
+    fn import(
+        &mut self,
+        reference: &str,
+        document: &Option<PathBuf>,
+        pointer: &str,
+    ) -> Result<String, Error> {
+        let location = self.locate(reference, document, pointer)?;
+        self.check_alias_cycle(&location, reference, pointer)?;
+        if location.0 == self.root {
+            let segments = pointer::split(&location.1);
+            if segments.len() == self.definitions.len() + 1
+                && segments.iter().zip(self.definitions).all(|(l, r)| l == r)
+            {
+                return Ok(format!("#{}", location.1));
+            }
+        }
+        if let Some(name) = self.imports.get(&location) {
+            return Ok(self.local_reference(name));
+        }
+        let mut target = self.fetch(&location, reference, pointer)?;
+        let name = self.unique_name(&location);
+        self.imports.insert(location.clone(), name.clone());
+        let target_pointer = self.display(&location);
+        self.walk(&mut target, &location.0, target_pointer, Node::Schema)?;
+        let result = self.local_reference(&name);
+        self.imported.push((name, target));
+        Ok(result)
+    }
+
+    /// Imports every schema of a `$defs` keyword, even the ones that are never referenced.
+    fn import_all(
+        &mut self,
+        definitions: &Value,
+        document: &Option<PathBuf>,
+        pointer: &str,
+    ) -> Result<(), Error> {
+        let fragment = &pointer[pointer.find('#').unwrap_or(0)..];
+        for name in definitions
+            .as_mapping()
+            .into_iter()
+            .flat_map(|m| m.iter().filter_map(|(k, _)| k.as_str()))
+        {
+            self.import(&pointer::join(fragment, name), document, pointer)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the object behind `reference`, with its own references resolved.
+    fn inline(
+        &mut self,
+        reference: &str,
+        document: &Option<PathBuf>,
+        pointer: &str,
+        node: Node,
+    ) -> Result<Value, Error> {
+        let location = self.locate(reference, document, pointer)?;
+        if self.inlining.contains(&location) {
+            return Err(Error::CyclicReference {
+                pointer: pointer.to_string(),
+                reference: reference.to_string(),
+            });
+        }
+        let mut target = self.fetch(&location, reference, pointer)?;
+        let target_pointer = self.display(&location);
+        let target_document = location.0.clone();
+        self.inlining.push(location);
+        let result = self.walk(&mut target, &target_document, target_pointer, node);
+        self.inlining.pop();
+        result.map(|_| target)
+    }
+
+    /// Fails if following `location` only leads to other references and eventually back to itself,
+    /// because such a chain of aliases never ends up in an actual schema.
+    fn check_alias_cycle(
+        &mut self,
+        location: &Location,
+        reference: &str,
+        pointer: &str,
+    ) -> Result<(), Error> {
+        let mut visited = vec![location.clone()];
+        loop {
+            let current = &visited[visited.len() - 1];
+            let next = match self
+                .lookup(current)
+                .and_then(Value::as_mapping)
+                .and_then(alias_of)
+            {
+                Some(next) => next.to_string(),
+                None => return Ok(()),
+            };
+            let document = current.0.clone();
+            let next = self.locate(&next, &document, pointer)?;
+            if visited.contains(&next) {
+                return Err(Error::CyclicReference {
+                    pointer: pointer.to_string(),
+                    reference: reference.to_string(),
+                });
+            }
+            visited.push(next);
+        }
+    }
+
+    /// Splits `reference` into the document it refers to, relative to `document`, and the pointer inside of it.
+    /// Loads the document if this is the first time it is referenced.
+    fn locate(
+        &mut self,
+        reference: &str,
+        document: &Option<PathBuf>,
+        pointer: &str,
+    ) -> Result<Location, Error> {
+        let unresolved = || Error::UnresolvedReference {
+            pointer: pointer.to_string(),
+            reference: reference.to_string(),
+        };
+        let (file, fragment) = match reference.find('#') {
+            Some(idx) => (&reference[..idx], &reference[idx + 1..]),
+            None => (reference, ""),
+        };
+        if !fragment.is_empty() && !fragment.starts_with('/') {
+            return Err(unresolved());
+        }
+        if file.is_empty() {
+            return Ok((document.clone(), fragment.to_string()));
+        }
+        if file.contains("://") {
+            return Err(unresolved());
+        }
+        let directory = document
+            .as_ref()
+            .and_then(|path| path.parent())
+            .ok_or_else(unresolved)?;
+        let path = directory
+            .join(file)
+            .canonicalize()
+            .map_err(|_| unresolved())?;
+        let path = Some(path);
+        if !self.documents.contains_key(&path) {
+            let file = File::open(path.as_ref().unwrap())?;
+            let value = serde_yaml::from_reader::<_, Value>(file)?;
+            self.documents.insert(path.clone(), value);
+        }
+        Ok((path, fragment.to_string()))
+    }
+
+    fn lookup(&self, location: &Location) -> Option<&Value> {
+        pointer::split(&location.1).iter().try_fold(
+            self.documents.get(&location.0)?,
+            |value, segment| match value {
+                Value::Sequence(s) => segment.parse::<usize>().ok().and_then(|idx| s.get(idx)),
+                Value::Mapping(m) => m.get(&Value::from(segment.as_str())),
+                _ => None,
+            },
+        )
+    }
+
+    fn fetch(&self, location: &Location, reference: &str, pointer: &str) -> Result<Value, Error> {
+        self.lookup(location)
+            .cloned()
+            .ok_or_else(|| Error::UnresolvedReference {
+                pointer: pointer.to_string(),
+                reference: reference.to_string(),
+            })
+    }
+
+    /// Derives a name for the imported schema from its location.
+    /// Direct definitions keep their name while nested fragments are named after their path,
+    /// e.g. `Pet.json#/properties/tag` becomes `PetTag`.
+    fn unique_name(&mut self, location: &Location) -> String {
+        let segments = pointer::split(&location.1);
+        let stem = location
+            .0
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string());
+        let direct = segments.len() > 1
+            && ["definitions", "schemas", "$defs"].contains(&segments[segments.len() - 2].as_str());
+        let parts = if segments.is_empty() {
+            stem.into_iter().collect::<Vec<_>>()
+        } else if direct {
+            vec![segments[segments.len() - 1].clone()]
+        } else {
+            let external = location.0 != self.root;
+            stem.filter(|_| external)
+                .into_iter()
+                .chain(
+                    segments
+                        .iter()
+                        .filter(|s| !STRUCTURAL_SEGMENTS.contains(&s.as_str()))
+                        .filter(|s| s.parse::<usize>().is_err())
+                        .cloned(),
+                )
+                .collect()
+        };
+        let base = parts
+            .iter()
+            .enumerate()
+            .map(|(idx, part)| {
+                if idx == 0 {
+                    part.clone()
+                } else {
+                    capitalize(part)
+                }
+            })
+            .collect::<String>();
+        let base = if base.is_empty() {
+            String::from("Schema")
+        } else {
+            base
+        };
+        let mut name = base.clone();
+        let mut counter = 2;
+        while self.names.contains(&name) {
+            name = format!("{}{}", base, counter);
+            counter += 1;
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    fn local_reference(&self, name: &str) -> String {
+        pointer::join(&format!("#/{}", self.definitions.join("/")), name)
+    }
+
+    fn display(&self, location: &Location) -> String {
+        match location.0.as_ref() {
+            Some(path) if location.0 != self.root => format!("{}#{}", path.display(), location.1),
+            _ => format!("#{}", location.1),
+        }
+    }
+
+    fn insert_imports(&mut self, document: &mut Value) {
+        if self.imported.is_empty() {
+            return;
+        }
+        let mut current = document;
+        for key in self.definitions {
+            if !matches!(current, Value::Mapping(_)) {
+                *current = Value::Mapping(Mapping::new());
+            }
+            let mapping = match current {
+                Value::Mapping(m) => m,
+                _ => unreachable!(),
+            };
+            current = mapping
+                .entry(Value::from(*key))
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+        }
+        if let Value::Mapping(definitions) = current {
+            for (name, schema) in self.imported.drain(..) {
+                definitions.insert(Value::from(name), schema);
+            }
+        }
+    }
+}
+
+/// Returns the reference of an object that is nothing but a reference.
+fn alias_of(mapping: &Mapping) -> Option<&str> {
+    if mapping.len() == 1 {
+        mapping.get(&Value::from("$ref")).and_then(Value::as_str)
+    } else {
+        None
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -1,5 +1,5 @@
 mod parse;
 mod spec;
 
-pub use parse::use_spec2;
+pub use parse::{use_spec2, use_spec2_paths};
 pub use spec::Spec2;
@@ -1,96 +1,508 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
 
-use super::spec::{Schema, Spec2};
-use crate::repr::{JavaScriptType, JavaScriptValue};
+use super::spec::{self, BooleanOrSchema, PathItem, Schema, Spec2};
+use crate::error::Error;
+use crate::ir::{
+    Additional, Definition, Field, FormatMappings, Kind, Metadata, Operation, Parameter,
+    ParameterLocation, RequestBody, Response, Type,
+};
+use crate::repr::{Documentation, JavaScriptValue};
+use crate::{naming, pointer};
+
+/// Everything the conversion of a schema needs to know about the rest of the document.
+pub struct Context<'a> {
+    /// Every definition, by name.
+    pub definitions: &'a BTreeMap<String, Schema>,
+    /// The type name of every definition.
+    pub names: &'a IndexMap<String, String>,
+    pub formats: &'a FormatMappings,
+    /// Whether schemas with an `x-brand` or a branded format are [`Kind::Branded`].
+    pub brands: bool,
+}
+
+pub fn convert_schema_to_documentation(schema: &Schema) -> Documentation {
+    let bound = |tag, exclusive_tag, bound: Option<f64>, exclusive: Option<bool>| {
+        bound.map(|b| {
+            if exclusive == Some(true) {
+                (exclusive_tag, b.to_string())
+            } else {
+                (tag, b.to_string())
+            }
+        })
+    };
+    let mut constraints = vec![];
+    constraints.extend(bound(
+        "minimum",
+        "exclusiveMinimum",
+        schema.minimum,
+        schema.exclusive_minimum,
+    ));
+    constraints.extend(bound(
+        "maximum",
+        "exclusiveMaximum",
+        schema.maximum,
+        schema.exclusive_maximum,
+    ));
+    constraints.extend(schema.multiple_of.map(|v| ("multipleOf", v.to_string())));
+    constraints.extend(schema.min_length.map(|v| ("minLength", v.to_string())));
+    constraints.extend(schema.max_length.map(|v| ("maxLength", v.to_string())));
+    constraints.extend(schema.pattern.clone().map(|v| ("pattern", v)));
+    constraints.extend(schema.min_items.map(|v| ("minItems", v.to_string())));
+    constraints.extend(schema.max_items.map(|v| ("maxItems", v.to_string())));
+    if schema.unique_items == Some(true) {
+        constraints.push(("uniqueItems", "true".to_string()));
+    }
+    Documentation {
+        title: schema.title.clone(),
+        description: schema.description.clone(),
+        deprecated: false,
+        default: schema.default.as_ref().map(JavaScriptValue::from),
+        examples: schema.example.iter().map(JavaScriptValue::from).collect(),
+        format: schema.format.clone(),
+        constraints,
+        enum_names: schema
+            .x_enum_varnames
+            .clone()
+            .or_else(|| schema.x_enum_names.clone())
+            .unwrap_or_default(),
+    }
+}
+
+pub fn convert_schema_to_metadata(schema: &Schema, pointer: &str) -> Metadata {
+    Metadata {
+        pointer: pointer.to_string(),
+        docs: convert_schema_to_documentation(schema),
+        nullable: schema.x_nullable == Some(true),
+        read_only: schema.read_only == Some(true),
+        write_only: false,
+    }
+}
 
 // TODO: Validate type at root is object?
-pub fn convert_schema_to_js_object_rows(
+pub fn convert_schema_to_fields(
     schema: &Schema,
-) -> HashMap<String, (bool, JavaScriptType)> {
+    ctx: &Context,
+    pointer: &str,
+) -> Result<IndexMap<String, Field>, Error> {
     if let Some(properties) = schema.properties.as_ref() {
         let required_names = schema.required.as_ref();
-        let properties = properties
+        let pointer = pointer::join(pointer, "properties");
+        properties
             .iter()
             .map(|(name, schema)| {
-                let required = required_names.map(|x| x.contains(&name)).unwrap_or(false);
-                let jtype = convert_schema_type_to_js_type(schema);
-                (name.clone(), (required, jtype))
+                let field = Field {
+                    required: required_names.map(|x| x.contains(name)).unwrap_or(false),
+                    schema: convert_schema(schema, ctx, &pointer::join(&pointer, name))?,
+                };
+                Ok((name.clone(), field))
             })
-            .collect::<HashMap<_, _>>();
-        properties
+            .collect()
     } else {
-        HashMap::new()
+        Ok(IndexMap::new())
     }
 }
 
-pub fn convert_schema_type_to_js_type(schema: &Schema) -> JavaScriptType {
+pub fn convert_schema_to_additional(
+    schema: &Schema,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Additional, Error> {
+    let pointer = pointer::join(pointer, "additionalProperties");
+    Ok(match schema.additional_properties.as_ref() {
+        None => Additional::Unspecified,
+        Some(BooleanOrSchema::Boolean(false)) => Additional::Forbidden,
+        Some(BooleanOrSchema::Boolean(true)) => {
+            Additional::Allowed(Box::new(Type::at(Kind::Any, &pointer)))
+        }
+        Some(BooleanOrSchema::Schema(s)) => {
+            Additional::Allowed(Box::new(convert_schema(s, ctx, &pointer)?))
+        }
+    })
+}
+
+pub fn convert_schema_to_object(
+    schema: &Schema,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Kind, Error> {
+    Ok(Kind::Object(
+        convert_schema_to_fields(schema, ctx, pointer)?,
+        convert_schema_to_additional(schema, ctx, pointer)?,
+    ))
+}
+
+pub fn convert_schema(schema: &Schema, ctx: &Context, pointer: &str) -> Result<Type, Error> {
+    let kind = convert_schema_to_kind(schema, ctx, pointer)?;
+    Ok(Type::new(
+        match schema.x_brand.as_ref() {
+            Some(brand) if ctx.brands => kind.branded(brand, pointer),
+            _ => kind,
+        },
+        convert_schema_to_metadata(schema, pointer),
+    ))
+}
+
+/// The values the schema accepts, besides `null` if it is nullable.
+fn convert_schema_to_kind(schema: &Schema, ctx: &Context, pointer: &str) -> Result<Kind, Error> {
     if let Some(r) = schema.ref_path.as_ref() {
-        JavaScriptType::Typename(parse_reference(r))
+        Ok(Kind::Reference(parse_reference(r, ctx, pointer)?))
     } else if let Some(all_of) = schema.all_of.as_ref() {
-        JavaScriptType::Product(
+        let pointer = pointer::join(pointer, "allOf");
+        Ok(Kind::Intersection(
             all_of
                 .iter()
-                .map(convert_schema_type_to_js_type)
-                .collect::<Vec<_>>(),
-        )
-    } else if let Some(jtype) = schema.schema_type.as_ref() {
-        match jtype.as_str() {
-            "integer" | "number" => JavaScriptType::typename("number"),
+                .enumerate()
+                .map(|(idx, s)| convert_schema(s, ctx, &pointer::join(&pointer, idx.to_string())))
+                .collect::<Result<Vec<_>, _>>()?,
+        ))
+    } else if let Some(ty) = schema.schema_type.as_ref() {
+        Ok(match ty.as_str() {
+            "integer" => ctx
+                .formats
+                .kind("integer", schema.format.as_deref(), Kind::Integer),
+            "number" => ctx
+                .formats
+                .kind("number", schema.format.as_deref(), Kind::Number),
             "string" => {
                 if let Some(enums) = schema.enum_values.as_ref() {
-                    JavaScriptType::Sum(
-                        enums
-                            .iter()
-                            .map(|v| {
-                                JavaScriptType::Value(Box::new(JavaScriptValue::String(
-                                    v.to_string(),
-                                )))
-                            })
-                            .collect(),
-                    )
-                } else if let Some(format) = schema.format.as_ref() {
-                    match format.as_str() {
-                        "date-time" => JavaScriptType::typename("Date"),
-                        _ => JavaScriptType::typename("string"),
-                    }
+                    Kind::Enum(enums.iter().map(JavaScriptValue::from).collect())
                 } else {
-                    JavaScriptType::typename("string")
+                    let kind = ctx
+                        .formats
+                        .kind("string", schema.format.as_deref(), Kind::String);
+                    if ctx.brands && schema.x_brand.is_none() {
+                        kind.branded_string(schema.format.as_deref(), pointer)
+                    } else {
+                        kind
+                    }
                 }
             }
-            "boolean" => JavaScriptType::typename("boolean"),
+            "boolean" => Kind::Boolean,
             "array" => match schema.items.as_ref() {
-                Some(child_schema) => {
-                    JavaScriptType::Array(Box::new(convert_schema_type_to_js_type(child_schema)))
-                }
-                None => JavaScriptType::typename("any"),
+                Some(child_schema) => Kind::Array(Box::new(convert_schema(
+                    child_schema,
+                    ctx,
+                    &pointer::join(pointer, "items"),
+                )?)),
+                None => Kind::Any,
             },
-            "object" => JavaScriptType::AnonymousObject(convert_schema_to_js_object_rows(schema)),
-            _ => JavaScriptType::typename("any"),
-        }
+            "object" => convert_schema_to_object(schema, ctx, pointer)?,
+            _ => Kind::Any,
+        })
     } else {
-        JavaScriptType::AnonymousObject(HashMap::new())
+        convert_schema_to_object(schema, ctx, pointer)
+    }
+}
+
+/// A definition extending a definition with a discriminator through `allOf` is one of its variants,
+/// so its discriminator is narrowed to its name.
+fn narrow_discriminated_subtype(ty: Type, name: &str, schema: &Schema, ctx: &Context) -> Type {
+    schema
+        .all_of
+        .iter()
+        .flatten()
+        .filter_map(|parent| parent.ref_path.as_deref())
+        .filter_map(|reference| reference.strip_prefix("#/definitions/"))
+        .filter_map(|parent| ctx.definitions.get(&pointer::unescape(parent)))
+        .filter_map(|parent| parent.discriminator.as_ref())
+        .fold(ty, |ty, property| {
+            ty.tagged(property, vec![JavaScriptValue::from(name)])
+        })
+}
+
+pub fn parse_schema(
+    (name, schema): (&String, &Schema),
+    ctx: &Context,
+) -> Result<Definition, Error> {
+    let pointer = pointer::join("#/definitions", name);
+    let ty = convert_schema(schema, ctx, &pointer)?;
+    Ok(Definition {
+        name: ctx
+            .names
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string()),
+        schema: narrow_discriminated_subtype(ty, name, schema, ctx).simplified(),
+    })
+}
+
+/// The type name of the definition a reference points to.
+pub fn parse_reference(reference: &str, ctx: &Context, pointer: &str) -> Result<String, Error> {
+    match reference.strip_prefix("#/definitions/") {
+        Some(name) if !name.is_empty() => {
+            let name = pointer::unescape(name);
+            Ok(ctx.names.get(&name).cloned().unwrap_or(name))
+        }
+        _ => Err(Error::UnresolvedReference {
+            pointer: pointer.to_string(),
+            reference: reference.to_string(),
+        }),
     }
 }
 
-pub fn parse_schema((name, schema): (&String, &Schema)) -> (String, JavaScriptType) {
-    let name = name.to_string();
-    let jtype = convert_schema_type_to_js_type(schema);
-    (name, jtype)
+/// The schema of a parameter other than the body, which is described by the parameter itself.
+fn convert_parameter_to_schema(parameter: &spec::Parameter) -> Schema {
+    Schema {
+        schema_type: parameter.parameter_type.clone(),
+        format: parameter.format.clone(),
+        items: parameter.items.clone(),
+        enum_values: parameter.enum_values.clone(),
+        default: parameter.default.clone(),
+        ..Schema::default()
+    }
 }
 
-pub fn parse_reference(reference: &str) -> String {
-    let (prefix, name) = reference.split_at("#/definitions/".len());
-    if prefix == "#/definitions/" {
-        String::from(name)
+fn convert_parameter_to_field(
+    parameter: &spec::Parameter,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Field, Error> {
+    let schema = match parameter.schema.as_ref() {
+        Some(schema) => convert_schema(schema, ctx, &pointer::join(pointer, "schema"))?,
+        None => convert_schema(&convert_parameter_to_schema(parameter), ctx, pointer)?,
+    }
+    .simplified();
+    let docs = schema.meta.docs;
+    Ok(Field {
+        // Path parameters are always required.
+        required: parameter.location == "path" || parameter.required == Some(true),
+        schema: Type::new(
+            schema.kind,
+            Metadata {
+                docs: Documentation {
+                    description: parameter.description.clone().or(docs.description),
+                    ..docs
+                },
+                ..schema.meta
+            },
+        ),
+    })
+}
+
+/// The style and explode of an OpenAPI 3 parameter equivalent to the `collectionFormat` of an array parameter.
+fn convert_collection_format(
+    location: ParameterLocation,
+    parameter: &spec::Parameter,
+) -> (String, bool) {
+    let collection_format = match parameter.parameter_type.as_deref() {
+        Some("array") => parameter.collection_format.as_deref().or(Some("csv")),
+        _ => None,
+    };
+    let (style, explode) = match (location, collection_format) {
+        (ParameterLocation::Query, Some("multi")) => ("form", true),
+        (ParameterLocation::Query, Some("ssv")) => ("spaceDelimited", false),
+        (ParameterLocation::Query, Some("pipes")) => ("pipeDelimited", false),
+        (ParameterLocation::Query, Some("tsv")) => ("tabDelimited", false),
+        (location, Some(_)) => (location.default_style(), false),
+        (location, None) => (location.default_style(), location.default_style() == "form"),
+    };
+    (style.to_string(), explode)
+}
+
+/// The media type the body is sent as, picked among the ones the operation consumes like OpenAPI 3 does.
+fn select_media_type(consumes: &[String], form: bool, files: bool) -> String {
+    let json = |media_type: &&String| {
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        essence == "application/json" || essence.ends_with("+json")
+    };
+    if form {
+        if files
+            || consumes
+                .iter()
+                .any(|m| m.starts_with("multipart/form-data"))
+        {
+            "multipart/form-data".to_string()
+        } else {
+            "application/x-www-form-urlencoded".to_string()
+        }
     } else {
-        let result = reference.to_string();
-        panic!("Unable to parse reference:'{}'", result);
+        consumes
+            .iter()
+            .find(json)
+            .or_else(|| consumes.first())
+            .cloned()
+            .unwrap_or_else(|| "application/json".to_string())
+    }
+}
+
+fn parse_operation(
+    (path, item): (&String, &PathItem),
+    (method, operation): (&str, &spec::Operation),
+    consumes: &[String],
+    ctx: &Context,
+) -> Result<Operation, Error> {
+    let path_pointer = pointer::join("#/paths", path);
+    let pointer = pointer::join(&path_pointer, method);
+    let mut parameters: Vec<(&spec::Parameter, String)> = vec![];
+    let shared = item
+        .parameters
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(idx, p)| {
+            let pointer =
+                pointer::join(&pointer::join(&path_pointer, "parameters"), idx.to_string());
+            (p, pointer)
+        });
+    let own = operation
+        .parameters
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(idx, p)| {
+            let pointer = pointer::join(&pointer::join(&pointer, "parameters"), idx.to_string());
+            (p, pointer)
+        });
+    for (parameter, pointer) in shared.chain(own) {
+        match parameters
+            .iter_mut()
+            .find(|(p, _)| p.name == parameter.name && p.location == parameter.location)
+        {
+            Some(overridden) => *overridden = (parameter, pointer),
+            None => parameters.push((parameter, pointer)),
+        }
+    }
+    let consumes = operation.consumes.as_deref().unwrap_or(consumes);
+    let mut request_body = None;
+    let mut form = IndexMap::new();
+    let mut files = false;
+    let mut params = vec![];
+    for (parameter, pointer) in parameters {
+        let field = convert_parameter_to_field(parameter, ctx, &pointer)?;
+        match parameter.location.as_str() {
+            "body" => {
+                request_body = Some(RequestBody {
+                    media_type: select_media_type(consumes, false, false),
+                    field,
+                })
+            }
+            "formData" => {
+                files |= parameter.parameter_type.as_deref() == Some("file");
+                form.insert(parameter.name.clone(), field);
+            }
+            location => {
+                let location =
+                    ParameterLocation::parse(location).ok_or_else(|| Error::UnsupportedSchema {
+                        pointer: pointer.clone(),
+                        reason: format!("unknown parameter location '{}'", location),
+                    })?;
+                let (style, explode) = convert_collection_format(location, parameter);
+                params.push(Parameter {
+                    name: parameter.name.clone(),
+                    location,
+                    style,
+                    explode,
+                    field,
+                })
+            }
+        }
     }
+    // Form parameters are the properties of the body.
+    if request_body.is_none() && !form.is_empty() {
+        request_body = Some(RequestBody {
+            media_type: select_media_type(consumes, true, files),
+            field: Field {
+                required: form.values().any(|f| f.required),
+                schema: Type::new(
+                    Kind::Object(form, Additional::Forbidden),
+                    Metadata::default(),
+                ),
+            },
+        });
+    }
+    let responses = operation
+        .responses
+        .iter()
+        .flatten()
+        .map(|(status, response)| {
+            let pointer = pointer::join(&pointer::join(&pointer, "responses"), status);
+            Ok(Response {
+                status: status.clone(),
+                schema: response
+                    .schema
+                    .as_ref()
+                    .map(|s| convert_schema(s, ctx, &pointer::join(&pointer, "schema")))
+                    .transpose()?
+                    .map(|ty| ty.simplified()),
+                docs: Documentation {
+                    description: response.description.clone(),
+                    ..Documentation::default()
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Operation {
+        name: naming::operation_name(operation.operation_id.as_deref(), method, path),
+        method: method.to_string(),
+        path: path.clone(),
+        docs: Documentation {
+            title: operation.summary.clone(),
+            description: operation.description.clone(),
+            deprecated: operation.deprecated == Some(true),
+            ..Documentation::default()
+        },
+        parameters: params,
+        request_body,
+        responses,
+        webhook: false,
+    })
 }
 
-pub fn use_spec2(spec: &Spec2) -> Vec<(String, JavaScriptType)> {
+/// The type of every definition, named after `names`.
+pub fn use_spec2(
+    spec: &Spec2,
+    names: &IndexMap<String, String>,
+    formats: &FormatMappings,
+    brands: bool,
+) -> Result<Vec<Definition>, Error> {
     match spec.definitions.as_ref() {
-        Some(definitions) => definitions.iter().map(parse_schema).collect(),
-        None => vec![],
+        Some(definitions) => {
+            let ctx = Context {
+                definitions,
+                names,
+                formats,
+                brands,
+            };
+            definitions
+                .iter()
+                .map(|definition| parse_schema(definition, &ctx))
+                .collect()
+        }
+        None => Ok(vec![]),
     }
 }
+
+/// Every operation of `paths`, referring to the definitions by their names in `names`.
+pub fn use_spec2_paths(
+    spec: &Spec2,
+    names: &IndexMap<String, String>,
+    formats: &FormatMappings,
+    brands: bool,
+) -> Result<Vec<Operation>, Error> {
+    let empty = BTreeMap::new();
+    let ctx = Context {
+        definitions: spec.definitions.as_ref().unwrap_or(&empty),
+        names,
+        formats,
+        brands,
+    };
+    let paths = spec.path_items()?;
+    paths
+        .iter()
+        .flat_map(|item| {
+            item.1
+                .operations()
+                .into_iter()
+                .map(move |operation| (item, operation))
+        })
+        .map(|(item, operation)| {
+            parse_operation(
+                item,
+                operation,
+                spec.consumes.as_deref().unwrap_or_default(),
+                &ctx,
+            )
+        })
+        .collect()
+}
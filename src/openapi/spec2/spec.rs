@@ -1,4 +1,6 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
 use std::collections::BTreeMap;
 
 /// top level document
@@ -8,6 +10,144 @@ pub struct Spec2 {
     pub swagger: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub definitions: Option<BTreeMap<String, Schema>>,
+    /// The media types the operations accept, unless they say otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumes: Option<Vec<String>>,
+    /// Every reference to a parameter or response is inlined.
+    /// Kept as is until the operations are generated, see [`Spec2::path_items`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Value>,
+}
+
+impl Spec2 {
+    /// The path items of `paths`, so that they only have to be valid when the operations are generated.
+    pub fn path_items(&self) -> Result<IndexMap<String, PathItem>, serde_yaml::Error> {
+        match &self.paths {
+            Some(paths) => serde_yaml::from_value(paths.clone()),
+            None => Ok(IndexMap::new()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
+pub struct PathItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub put: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Operation>,
+    /// Parameters shared by all the operations of the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Parameter>>,
+}
+
+impl PathItem {
+    /// The operations of the path with their method.
+    pub fn operations(&self) -> Vec<(&'static str, &Operation)> {
+        vec![
+            ("get", &self.get),
+            ("put", &self.put),
+            ("post", &self.post),
+            ("delete", &self.delete),
+            ("options", &self.options),
+            ("head", &self.head),
+            ("patch", &self.patch),
+        ]
+        .into_iter()
+        .filter_map(|(method, operation)| operation.as_ref().map(|o| (method, o)))
+        .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
+pub struct Operation {
+    #[serde(rename = "operationId", skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Parameter>>,
+    /// Responses by status code, which may be written as a number.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_responses",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub responses: Option<IndexMap<String, Response>>,
+}
+
+/// A parameter of an operation.
+/// The body is described by `schema`, the other parameters by `type` and the keywords that go with it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
+pub struct Parameter {
+    pub name: String,
+    /// One of `path`, `query`, `header`, `body` or `formData`.
+    #[serde(rename = "in")]
+    pub location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Schema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "type")]
+    pub parameter_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "enum")]
+    pub enum_values: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// How arrays are serialized: `csv` (the default), `ssv`, `tsv`, `pipes` or `multi`.
+    #[serde(rename = "collectionFormat", skip_serializing_if = "Option::is_none")]
+    pub collection_format: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
+pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Schema>,
+}
+
+fn deserialize_responses<'de, D>(
+    deserializer: D,
+) -> Result<Option<IndexMap<String, Response>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<IndexMap<Value, Response>>::deserialize(deserializer)?
+        .map(|responses| {
+            responses
+                .into_iter()
+                .map(|(status, response)| match status {
+                    Value::Number(n) => Ok((n.to_string(), response)),
+                    Value::String(s) => Ok((s, response)),
+                    _ => Err(serde::de::Error::custom("invalid status code")),
+                })
+                .collect()
+        })
+        .transpose()
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
@@ -18,22 +158,80 @@ pub struct Schema {
     #[serde(rename = "$ref")]
     pub ref_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "type")]
     pub schema_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
+    #[serde(rename = "multipleOf", skip_serializing_if = "Option::is_none")]
+    pub multiple_of: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_minimum: Option<bool>,
+    #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_maximum: Option<bool>,
+    #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u64>,
+    #[serde(rename = "maxLength", skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(rename = "maxItems", skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(rename = "uniqueItems", skip_serializing_if = "Option::is_none")]
+    pub unique_items: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "enum")]
-    pub enum_values: Option<Vec<String>>,
+    pub enum_values: Option<Vec<Value>>,
+    /// Names of the `enum` values, a vendor extension used by various generators.
+    #[serde(rename = "x-enum-varnames", skip_serializing_if = "Option::is_none")]
+    pub x_enum_varnames: Option<Vec<String>>,
+    /// Same as `x-enum-varnames`.
+    #[serde(rename = "x-enumNames", skip_serializing_if = "Option::is_none")]
+    pub x_enum_names: Option<Vec<String>>,
+    /// The name of the nominal type the values are generated as when branded types are enabled.
+    #[serde(rename = "x-brand", skip_serializing_if = "Option::is_none")]
+    pub x_brand: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub items: Option<Box<Schema>>,
     // implies object
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub properties: Option<BTreeMap<String, Schema>>,
+    pub properties: Option<IndexMap<String, Schema>>,
     #[serde(rename = "allOf", skip_serializing_if = "Option::is_none")]
     pub all_of: Option<Vec<Schema>>,
+    #[serde(
+        rename = "additionalProperties",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub additional_properties: Option<BooleanOrSchema>,
+    #[serde(rename = "readOnly", skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    /// Swagger 2.0 has no notion of `null`, this vendor extension is the usual workaround.
+    #[serde(rename = "x-nullable", skip_serializing_if = "Option::is_none")]
+    pub x_nullable: Option<bool>,
+    /// The name of the property telling which of the definitions extending this one through `allOf` a value is.
+    /// Its value is the name of that definition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum BooleanOrSchema {
+    Boolean(bool),
+    Schema(Box<Schema>),
 }
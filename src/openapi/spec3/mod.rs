@@ -1,5 +1,5 @@
 mod parse;
 mod spec;
 
-pub use parse::use_spec3;
+pub use parse::{use_spec3, use_spec3_paths};
 pub use spec::Spec3;
@@ -1,162 +1,826 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
 
-use super::spec::{ObjectOrReference, Schema, Spec3};
-use crate::repr::{JavaScriptType, JavaScriptValue};
+use super::spec::{
+    self, BooleanObjectOrReference, Discriminator, MediaType, ObjectOrReference, PathItem, Schema,
+    Spec3,
+};
+use crate::error::Error;
+use crate::ir::{
+    Additional, Definition, Field, FormatMappings, Kind, Metadata, Operation, Parameter,
+    ParameterLocation, RequestBody, Response, Type,
+};
+use crate::repr::{Documentation, JavaScriptValue};
+use crate::{naming, pointer};
 
-pub fn parse_reference(reference: &str) -> String {
-    let (prefix, name) = reference.split_at("#/components/schemas/".len());
-    if prefix == "#/components/schemas/" {
-        String::from(name)
+/// The type name of the schema a reference points to.
+pub fn parse_reference(reference: &str, ctx: &Context, pointer: &str) -> Result<String, Error> {
+    match reference.strip_prefix("#/components/schemas/") {
+        Some(name) if !name.is_empty() => {
+            let name = pointer::unescape(name);
+            Ok(ctx.names.get(&name).cloned().unwrap_or(name))
+        }
+        _ => Err(Error::UnresolvedReference {
+            pointer: pointer.to_string(),
+            reference: reference.to_string(),
+        }),
+    }
+}
+
+/// The minor version of the specification, for the few places where 3.0 and 3.1 differ.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Version {
+    V3_0,
+    V3_1,
+}
+
+/// Everything the conversion of a schema needs to know about the rest of the document.
+pub struct Context<'a> {
+    pub version: Version,
+    /// Every schema of `components`, by name.
+    pub schemas: &'a BTreeMap<String, ObjectOrReference<Schema>>,
+    /// The type name of every schema of `components`.
+    pub names: &'a IndexMap<String, String>,
+    pub formats: &'a FormatMappings,
+    /// Whether schemas with an `x-brand` or a branded format are [`Kind::Branded`].
+    pub brands: bool,
+}
+
+/// The values the discriminator takes for the schema called `name`.
+/// Those are the keys of the mapping that point to the schema, or its name if there are none.
+fn parse_discriminator_values(discriminator: &Discriminator, name: &str) -> Vec<JavaScriptValue> {
+    let values = discriminator
+        .mapping
+        .iter()
+        .flatten()
+        .filter(|(_, target)| {
+            let target = pointer::unescape(target.rsplit('/').next().unwrap_or(target));
+            let target = [".json", ".yaml", ".yml"]
+                .iter()
+                .fold(target.as_str(), |t, ext| t.strip_suffix(ext).unwrap_or(t))
+                .to_string();
+            target == name
+        })
+        .map(|(value, _)| JavaScriptValue::from(value.as_str()))
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        vec![JavaScriptValue::from(name)]
     } else {
-        let result = reference.to_string();
-        panic!("Unable to parse reference:'{}'", result);
+        values
     }
 }
 
-fn parse_schema_object_to_js_arrays(schema: &Schema) -> JavaScriptType {
-    if let Some(x) = schema.items.as_ref() {
-        match x.as_ref() {
-            ObjectOrReference::Object(o) => parse_schema_object_to_js_type(o),
-            ObjectOrReference::Ref(s) => JavaScriptType::Typename(parse_reference(&s.ref_path)),
-        }
+/// Narrows the discriminator of every variant of a `oneOf`/`anyOf` that refers to a named schema.
+fn parse_schema_variants(
+    variants: &[ObjectOrReference<Schema>],
+    discriminator: Option<&Discriminator>,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Kind, Error> {
+    let variants = parse_schema_objects(variants, ctx, pointer)?;
+    Ok(Kind::Union(match discriminator {
+        Some(discriminator) => variants
+            .into_iter()
+            .map(|variant| {
+                let values = match &variant.kind {
+                    Kind::Reference(name) => {
+                        // The mapping refers to the schema by its key rather than its type name.
+                        let key = ctx
+                            .names
+                            .iter()
+                            .find(|(_, n)| *n == name)
+                            .map_or(name, |(key, _)| key);
+                        Some(parse_discriminator_values(discriminator, key))
+                    }
+                    _ => None,
+                };
+                match values {
+                    Some(values) => variant.tagged(&discriminator.property_name, values),
+                    None => variant,
+                }
+            })
+            .collect(),
+        None => variants,
+    }))
+}
+
+/// A schema extending a schema with a discriminator through `allOf` is one of its variants,
+/// so its discriminator is narrowed to the value identifying it.
+fn narrow_discriminated_subtype(ty: Type, name: &str, schema: &Schema, ctx: &Context) -> Type {
+    schema
+        .all_of
+        .iter()
+        .flatten()
+        .filter_map(|parent| match parent {
+            ObjectOrReference::Ref(r) => Some(r.ref_path.as_str()),
+            ObjectOrReference::Object(o) => o.ref_path.as_deref(),
+        })
+        .filter_map(|reference| reference.strip_prefix("#/components/schemas/"))
+        .filter_map(|parent| match ctx.schemas.get(&pointer::unescape(parent)) {
+            Some(ObjectOrReference::Object(parent)) => parent.discriminator.as_ref(),
+            _ => None,
+        })
+        .fold(ty, |ty, discriminator| {
+            let values = parse_discriminator_values(discriminator, name);
+            ty.tagged(&discriminator.property_name, values)
+        })
+}
+
+fn parse_object_or_reference(
+    schema: &ObjectOrReference<Schema>,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Type, Error> {
+    match schema {
+        ObjectOrReference::Object(o) => parse_schema_object(o, ctx, pointer),
+        ObjectOrReference::Ref(r) => Ok(Type::at(
+            Kind::Reference(parse_reference(&r.ref_path, ctx, pointer)?),
+            pointer,
+        )),
+    }
+}
+
+fn parse_boolean_object_or_reference(
+    schema: &BooleanObjectOrReference<Box<Schema>>,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Type, Error> {
+    match schema {
+        BooleanObjectOrReference::Boolean(true) => Ok(Type::at(Kind::Any, pointer)),
+        BooleanObjectOrReference::Boolean(false) => Ok(Type::at(Kind::Never, pointer)),
+        BooleanObjectOrReference::Object(o) => parse_schema_object(o, ctx, pointer),
+        BooleanObjectOrReference::Ref(r) => Ok(Type::at(
+            Kind::Reference(parse_reference(&r.ref_path, ctx, pointer)?),
+            pointer,
+        )),
+    }
+}
+
+fn parse_schema_object_to_array(
+    schema: &Schema,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Kind, Error> {
+    let items = schema
+        .items
+        .as_ref()
+        .map(|x| parse_boolean_object_or_reference(x, ctx, &pointer::join(pointer, "items")))
+        .transpose()?;
+    if let Some(prefix_items) = schema.prefix_items.as_ref() {
+        let prefix_pointer = pointer::join(pointer, "prefixItems");
+        let prefix_items = prefix_items
+            .iter()
+            .enumerate()
+            .map(|(idx, x)| {
+                parse_object_or_reference(x, ctx, &pointer::join(&prefix_pointer, idx.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Without `items`, any element may follow the prefix.
+        let rest = match items {
+            Some(Type {
+                kind: Kind::Never, ..
+            }) => None,
+            Some(items) => Some(Box::new(items)),
+            None => Some(Box::new(Type::at(Kind::Any, pointer))),
+        };
+        Ok(Kind::Tuple(prefix_items, rest))
+    } else if let Some(items) = items {
+        Ok(Kind::Array(Box::new(items)))
+    } else if ctx.version == Version::V3_1 {
+        Ok(Kind::Array(Box::new(Type::at(Kind::Any, pointer))))
     } else {
-        panic!("Unable to convert schema to javascript array")
+        Err(Error::UnsupportedSchema {
+            pointer: pointer.to_string(),
+            reason: "an array schema must specify 'items'".to_string(),
+        })
     }
 }
 
-fn parse_schema_object_to_js_string(schema: &Schema) -> JavaScriptType {
+fn parse_schema_object_to_string(schema: &Schema, ctx: &Context, pointer: &str) -> Kind {
     if let Some(enums) = schema.enum_values.as_ref() {
-        JavaScriptType::Sum(
-            enums
-                .iter()
-                .map(|v| JavaScriptType::Value(Box::new(parse_json_value_to_javascript_type(v))))
-                .collect(),
-        )
+        Kind::Enum(enums.iter().map(JavaScriptValue::from).collect())
     } else {
-        // TODO(hbina): Handle the case for enums
-        if let Some(format) = schema.format.as_ref() {
-            match format.as_str() {
-                "date" | "date-time" => JavaScriptType::typename("Date"),
-                _ => JavaScriptType::typename("string"),
-            }
+        let kind = ctx
+            .formats
+            .kind("string", schema.format.as_deref(), Kind::String);
+        if ctx.brands && schema.x_brand.is_none() {
+            kind.branded_string(schema.format.as_deref(), pointer)
         } else {
-            JavaScriptType::typename("string")
+            kind
+        }
+    }
+}
+
+/// A bound is exclusive if `exclusiveMinimum`/`exclusiveMaximum` is `true` (OpenAPI 3.0)
+/// or is itself the bound (OpenAPI 3.1).
+fn parse_bound_to_constraints(
+    constraints: &mut Vec<(&'static str, String)>,
+    (tag, bound): (&'static str, Option<f64>),
+    (exclusive_tag, exclusive): (&'static str, Option<&serde_yaml::Value>),
+) {
+    match (bound, exclusive) {
+        (Some(bound), Some(serde_yaml::Value::Bool(true))) => {
+            constraints.push((exclusive_tag, bound.to_string()))
+        }
+        (bound, exclusive) => {
+            constraints.extend(bound.map(|b| (tag, b.to_string())));
+            constraints.extend(
+                exclusive
+                    .and_then(serde_yaml::Value::as_f64)
+                    .map(|b| (exclusive_tag, b.to_string())),
+            );
         }
     }
 }
 
-fn parse_schema_object_to_js_object_row(
+fn parse_schema_object_to_documentation(schema: &Schema) -> Documentation {
+    let mut constraints = vec![];
+    parse_bound_to_constraints(
+        &mut constraints,
+        ("minimum", schema.minimum),
+        ("exclusiveMinimum", schema.exclusive_minimum.as_ref()),
+    );
+    parse_bound_to_constraints(
+        &mut constraints,
+        ("maximum", schema.maximum),
+        ("exclusiveMaximum", schema.exclusive_maximum.as_ref()),
+    );
+    constraints.extend(schema.multiple_of.map(|v| ("multipleOf", v.to_string())));
+    constraints.extend(schema.min_length.map(|v| ("minLength", v.to_string())));
+    constraints.extend(schema.max_length.map(|v| ("maxLength", v.to_string())));
+    constraints.extend(schema.pattern.clone().map(|v| ("pattern", v)));
+    constraints.extend(schema.min_items.map(|v| ("minItems", v.to_string())));
+    constraints.extend(schema.max_items.map(|v| ("maxItems", v.to_string())));
+    if schema.unique_items == Some(true) {
+        constraints.push(("uniqueItems", "true".to_string()));
+    }
+    Documentation {
+        title: schema.title.clone(),
+        description: schema.description.clone(),
+        deprecated: schema.deprecated == Some(true),
+        default: schema.default.as_ref().map(JavaScriptValue::from),
+        examples: schema
+            .example
+            .iter()
+            .chain(schema.examples.iter().flatten())
+            .map(JavaScriptValue::from)
+            .collect(),
+        format: schema.format.clone(),
+        constraints,
+        enum_names: schema
+            .x_enum_varnames
+            .clone()
+            .or_else(|| schema.x_enum_names.clone())
+            .unwrap_or_default(),
+    }
+}
+
+fn parse_schema_object_to_metadata(schema: &Schema, pointer: &str) -> Metadata {
+    Metadata {
+        pointer: pointer.to_string(),
+        docs: parse_schema_object_to_documentation(schema),
+        nullable: schema.nullable == Some(true),
+        read_only: schema.read_only == Some(true),
+        write_only: schema.write_only == Some(true),
+    }
+}
+
+fn parse_schema_object_to_fields(
     schema: &Schema,
-) -> HashMap<String, (bool, JavaScriptType)> {
-    // 1. Find the required properties.
-    // 2. Iterate through properties.
-    // 3. Parse each rows type, creating a triplet of (name, required, type)
+    ctx: &Context,
+    pointer: &str,
+) -> Result<IndexMap<String, Field>, Error> {
     if let Some(properties) = schema.properties.as_ref() {
         let required = schema.required.as_ref();
-        let result = properties
+        let pointer = pointer::join(pointer, "properties");
+        properties
             .iter()
             .map(|(name, object)| {
                 let name = name.to_string();
-                let row_required = required.map(|r| r.contains(&name)).unwrap_or(false);
-                let jtype = match object {
-                    ObjectOrReference::Ref(r) => {
-                        JavaScriptType::Typename(parse_reference(&r.ref_path))
-                    }
-                    ObjectOrReference::Object(o) => parse_schema_object_to_js_type(o),
+                let field = Field {
+                    required: required.map(|r| r.contains(&name)).unwrap_or(false),
+                    schema: parse_object_or_reference(
+                        object,
+                        ctx,
+                        &pointer::join(&pointer, &name),
+                    )?,
                 };
-                (name, (row_required, jtype))
+                Ok((name, field))
             })
-            .collect::<HashMap<_, _>>();
-        result
+            .collect()
     } else {
-        HashMap::new()
+        Ok(IndexMap::new())
     }
 }
 
-fn parse_schema_object_to_js_enum_values(schema: &Schema) -> Vec<JavaScriptType> {
-    if let Some(values) = schema.enum_values.as_ref() {
-        values
-            .iter()
-            .map(|v| JavaScriptType::Value(Box::new(parse_json_value_to_javascript_type(v))))
-            .collect()
+fn parse_schema_object_to_additional(
+    schema: &Schema,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Additional, Error> {
+    Ok(match schema.additional_properties.as_ref() {
+        None => Additional::Unspecified,
+        Some(BooleanObjectOrReference::Boolean(false)) => Additional::Forbidden,
+        Some(additional) => Additional::Allowed(Box::new(parse_boolean_object_or_reference(
+            additional,
+            ctx,
+            &pointer::join(pointer, "additionalProperties"),
+        )?)),
+    })
+}
+
+fn parse_schema_object_to_enum_values(schema: &Schema) -> Vec<JavaScriptValue> {
+    schema
+        .enum_values
+        .iter()
+        .flatten()
+        .map(JavaScriptValue::from)
+        .collect()
+}
+
+/// Whether the schema says anything about the shape of the value.
+/// Keywords like `description` don't, so a `$ref` with only those as siblings is just the referenced type.
+fn has_assertions(schema: &Schema) -> bool {
+    schema.schema_type.is_some()
+        || schema.const_value.is_some()
+        || schema.enum_values.is_some()
+        || schema.properties.is_some()
+        || schema.additional_properties.is_some()
+        || schema.items.is_some()
+        || schema.prefix_items.is_some()
+        || schema.all_of.is_some()
+        || schema.one_of.is_some()
+        || schema.any_of.is_some()
+        || schema.not.is_some()
+}
+
+fn has_composition(schema: &Schema) -> bool {
+    schema.all_of.is_some()
+        || schema.one_of.is_some()
+        || schema.any_of.is_some()
+        || schema.not.is_some()
+}
+
+fn parse_schema_objects(
+    schemas: &[ObjectOrReference<Schema>],
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Vec<Type>, Error> {
+    schemas
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| parse_object_or_reference(s, ctx, &pointer::join(pointer, idx.to_string())))
+        .collect()
+}
+
+/// A value must satisfy the rest of the schema and every one of its `allOf`, `oneOf`, `anyOf` and `not`,
+/// so all of them are intersected.
+fn parse_schema_object_composition(
+    schema: &Schema,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Kind, Error> {
+    let base = Schema {
+        all_of: None,
+        one_of: None,
+        any_of: None,
+        not: None,
+        nullable: None,
+        x_brand: None,
+        ..schema.clone()
+    };
+    let mut parts = vec![];
+    if has_assertions(&base) {
+        parts.push(parse_schema_object(&base, ctx, pointer)?);
+    }
+    if let Some(all_of) = schema.all_of.as_ref() {
+        parts.extend(parse_schema_objects(
+            all_of,
+            ctx,
+            &pointer::join(pointer, "allOf"),
+        )?);
+    }
+    if let Some(one_of) = schema.one_of.as_ref() {
+        let pointer = pointer::join(pointer, "oneOf");
+        parts.push(Type::at(
+            parse_schema_variants(one_of, schema.discriminator.as_ref(), ctx, &pointer)?,
+            &pointer,
+        ));
+    }
+    if let Some(any_of) = schema.any_of.as_ref() {
+        let pointer = pointer::join(pointer, "anyOf");
+        parts.push(Type::at(
+            parse_schema_variants(any_of, schema.discriminator.as_ref(), ctx, &pointer)?,
+            &pointer,
+        ));
+    }
+    if let Some(not) = schema.not.as_ref() {
+        parts.push(Type::at(
+            Kind::Not(Box::new(parse_object_or_reference(
+                not,
+                ctx,
+                &pointer::join(pointer, "not"),
+            )?)),
+            pointer,
+        ));
+    }
+    // The schema is the only part if it is all there is, e.g. an `allOf` of one reference.
+    Ok(if parts.len() == 1 {
+        parts.remove(0).kind
     } else {
-        vec![]
-    }
-}
-
-// TODO(hbina): Reimplement this to return an intermediate object so we can log the transformation.
-pub fn parse_schema_object_to_js_type(schema: &Schema) -> JavaScriptType {
-    if let Some(ty) = schema.schema_type.as_ref() {
-        match ty.as_str() {
-            "array" => JavaScriptType::Array(Box::new(parse_schema_object_to_js_arrays(schema))),
-            "string" => parse_schema_object_to_js_string(schema),
-            "object" => {
-                JavaScriptType::AnonymousObject(parse_schema_object_to_js_object_row(schema))
-            }
-            // TODO(hbina): Narrow down the exact type later.
-            "integer" | "number" => JavaScriptType::typename("number"),
-            "boolean" => JavaScriptType::typename("boolean"),
-            "unknown" => JavaScriptType::typename("unknown"),
-            "enum" => JavaScriptType::Sum(parse_schema_object_to_js_enum_values(schema)),
-            // TODO(hbina): It is entirely possile type of a schema object to just be a string.
-            // I should think.
-            // Actually, this case should not even be possible because `types` can take a limited set of values.
-            // Rework `openapi` to make this unrepresentable.
-            anon => JavaScriptType::Typename(anon.to_string()),
+        Kind::Intersection(parts)
+    })
+}
+
+fn parse_schema_object_with_type(
+    schema: &Schema,
+    ty: &str,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Kind, Error> {
+    Ok(match ty {
+        "array" => parse_schema_object_to_array(schema, ctx, pointer)?,
+        "string" => parse_schema_object_to_string(schema, ctx, pointer),
+        "object" => Kind::Object(
+            parse_schema_object_to_fields(schema, ctx, pointer)?,
+            parse_schema_object_to_additional(schema, ctx, pointer)?,
+        ),
+        "integer" => ctx
+            .formats
+            .kind(ty, schema.format.as_deref(), Kind::Integer),
+        "number" => ctx.formats.kind(ty, schema.format.as_deref(), Kind::Number),
+        "boolean" => Kind::Boolean,
+        "null" => Kind::Null,
+        "unknown" => Kind::Unknown,
+        "enum" => Kind::Enum(parse_schema_object_to_enum_values(schema)),
+        other => Kind::Other(other.to_string()),
+    })
+}
+
+pub fn parse_schema_object(schema: &Schema, ctx: &Context, pointer: &str) -> Result<Type, Error> {
+    let kind = parse_schema_object_to_kind(schema, ctx, pointer)?;
+    Ok(Type::new(
+        match schema.x_brand.as_ref() {
+            Some(brand) if ctx.brands => kind.branded(brand, pointer),
+            _ => kind,
+        },
+        parse_schema_object_to_metadata(schema, pointer),
+    ))
+}
+
+/// The values the schema accepts, besides `null` if it is nullable.
+fn parse_schema_object_to_kind(
+    schema: &Schema,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Kind, Error> {
+    if let Some(reference) = schema.ref_path.as_ref() {
+        let target = Type::at(
+            Kind::Reference(parse_reference(reference, ctx, pointer)?),
+            pointer,
+        );
+        let siblings = Schema {
+            ref_path: None,
+            nullable: None,
+            x_brand: None,
+            ..schema.clone()
+        };
+        if has_assertions(&siblings) {
+            Ok(Kind::Intersection(vec![
+                target,
+                parse_schema_object(&siblings, ctx, pointer)?,
+            ]))
+        } else {
+            Ok(target.kind)
+        }
+    } else if has_composition(schema) {
+        parse_schema_object_composition(schema, ctx, pointer)
+    } else if let Some(value) = schema.const_value.as_ref() {
+        Ok(Kind::Const(JavaScriptValue::from(value)))
+    } else if let Some(ty) = schema.schema_type.as_ref() {
+        match ty.types().as_slice() {
+            [ty] => parse_schema_object_with_type(schema, ty, ctx, pointer),
+            types => Ok(Kind::Union(
+                types
+                    .iter()
+                    .map(|ty| {
+                        Ok(Type::new(
+                            parse_schema_object_with_type(schema, ty, ctx, pointer)?,
+                            Metadata {
+                                nullable: false,
+                                ..parse_schema_object_to_metadata(schema, pointer)
+                            },
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?,
+            )),
         }
+    } else if schema.properties.is_some() || schema.additional_properties.is_some() {
+        parse_schema_object_with_type(schema, "object", ctx, pointer)
+    } else if schema.items.is_some() || schema.prefix_items.is_some() {
+        parse_schema_object_with_type(schema, "array", ctx, pointer)
+    } else if schema.enum_values.is_some() {
+        parse_schema_object_with_type(schema, "enum", ctx, pointer)
     } else {
         // TODO(hbina): Revisit this case.
         // The specification does not say anything about the absent of this value.
         // It might be inherited from JSON SchemaObject. Look it up.
-        JavaScriptType::typename("any")
+        Ok(Kind::Any)
     }
 }
 
 pub fn parse_schema(
     (name, schema): (&String, &ObjectOrReference<Schema>),
-) -> (String, JavaScriptType) {
-    let name = name.to_string();
-    let jtype = match schema {
-        ObjectOrReference::Ref(s) => JavaScriptType::Typename(parse_reference(&s.ref_path)),
-        ObjectOrReference::Object(v) => parse_schema_object_to_js_type(v),
+    ctx: &Context,
+) -> Result<Definition, Error> {
+    let pointer = pointer::join("#/components/schemas", name);
+    let ty = parse_object_or_reference(schema, ctx, &pointer)?;
+    let ty = match schema {
+        ObjectOrReference::Object(schema) => narrow_discriminated_subtype(ty, name, schema, ctx),
+        ObjectOrReference::Ref(_) => ty,
+    };
+    Ok(Definition {
+        name: ctx
+            .names
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string()),
+        schema: ty.simplified(),
+    })
+}
+
+/// The media type of `content` whose type is generated, the JSON one or else the first one.
+fn select_media_type(content: &IndexMap<String, MediaType>) -> Option<(&String, &MediaType)> {
+    content
+        .iter()
+        .find(|(media_type, _)| {
+            let essence = media_type.split(';').next().unwrap_or_default().trim();
+            essence == "application/json" || essence.ends_with("+json")
+        })
+        .or_else(|| content.iter().next())
+}
+
+/// The media type and the type of the body described by `content`, if it has any media type.
+fn parse_content_to_type(
+    content: &IndexMap<String, MediaType>,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Option<(String, Type)>, Error> {
+    select_media_type(content)
+        .map(|(media_type, media)| {
+            let pointer = pointer::join(pointer, media_type);
+            let ty = match media.schema.as_ref() {
+                Some(schema) => {
+                    parse_object_or_reference(schema, ctx, &pointer::join(&pointer, "schema"))?
+                        .simplified()
+                }
+                None => Type::at(Kind::Any, &pointer),
+            };
+            Ok((media_type.clone(), ty))
+        })
+        .transpose()
+}
+
+fn parse_parameter(
+    parameter: &spec::Parameter,
+    ctx: &Context,
+    pointer: &str,
+) -> Result<Parameter, Error> {
+    let location =
+        ParameterLocation::parse(&parameter.location).ok_or_else(|| Error::UnsupportedSchema {
+            pointer: pointer.to_string(),
+            reason: format!("unknown parameter location '{}'", parameter.location),
+        })?;
+    let schema = match (parameter.schema.as_ref(), parameter.content.as_ref()) {
+        (Some(schema), _) => {
+            parse_object_or_reference(schema, ctx, &pointer::join(pointer, "schema"))?.simplified()
+        }
+        (None, Some(content)) => {
+            let (kind, meta) =
+                parse_content_to_type(content, ctx, &pointer::join(pointer, "content"))?
+                    .map_or((Kind::Any, Metadata::default()), |(_, ty)| {
+                        (ty.kind, ty.meta)
+                    });
+            // The documentation of the content's schema isn't the parameter's.
+            Type::new(
+                kind,
+                Metadata {
+                    docs: Documentation::default(),
+                    ..meta
+                },
+            )
+        }
+        (None, None) => Type::at(Kind::Any, pointer),
     };
-    (name, jtype)
+    let docs = schema.meta.docs;
+    let style = parameter
+        .style
+        .clone()
+        .unwrap_or_else(|| location.default_style().to_string());
+    Ok(Parameter {
+        name: parameter.name.clone(),
+        location,
+        explode: parameter.explode.unwrap_or(style == "form"),
+        style,
+        field: Field {
+            // Path parameters are always required.
+            required: location == ParameterLocation::Path || parameter.required == Some(true),
+            schema: Type::new(
+                schema.kind,
+                Metadata {
+                    docs: Documentation {
+                        description: parameter.description.clone().or(docs.description),
+                        deprecated: docs.deprecated || parameter.deprecated == Some(true),
+                        ..docs
+                    },
+                    ..schema.meta
+                },
+            ),
+        },
+    })
+}
+
+/// Parses an operation of `paths`, or of `webhooks` if `webhook`, where `path` is the name of the webhook.
+fn parse_operation(
+    (path, item): (&String, &PathItem),
+    (method, operation): (&str, &spec::Operation),
+    webhook: bool,
+    ctx: &Context,
+) -> Result<Operation, Error> {
+    let path_pointer = pointer::join(if webhook { "#/webhooks" } else { "#/paths" }, path);
+    let pointer = pointer::join(&path_pointer, method);
+    let mut parameters = item
+        .parameters
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(idx, p)| {
+            parse_parameter(
+                p,
+                ctx,
+                &pointer::join(&pointer::join(&path_pointer, "parameters"), idx.to_string()),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for (idx, parameter) in operation.parameters.iter().flatten().enumerate() {
+        let parameter = parse_parameter(
+            parameter,
+            ctx,
+            &pointer::join(&pointer::join(&pointer, "parameters"), idx.to_string()),
+        )?;
+        match parameters
+            .iter_mut()
+            .find(|p| p.name == parameter.name && p.location == parameter.location)
+        {
+            Some(overridden) => *overridden = parameter,
+            None => parameters.push(parameter),
+        }
+    }
+    let request_body = operation
+        .request_body
+        .as_ref()
+        .map(|body| {
+            let pointer = pointer::join(&pointer, "requestBody");
+            let (media_type, ty) =
+                parse_content_to_type(&body.content, ctx, &pointer::join(&pointer, "content"))?
+                    .unwrap_or_else(|| {
+                        (
+                            "application/json".to_string(),
+                            Type::at(Kind::Any, &pointer),
+                        )
+                    });
+            Ok::<_, Error>(RequestBody {
+                media_type,
+                field: Field {
+                    required: body.required == Some(true),
+                    schema: Type::new(
+                        ty.kind,
+                        Metadata {
+                            docs: Documentation {
+                                description: body.description.clone(),
+                                ..Documentation::default()
+                            },
+                            ..ty.meta
+                        },
+                    ),
+                },
+            })
+        })
+        .transpose()?;
+    let responses = operation
+        .responses
+        .iter()
+        .flatten()
+        .map(|(status, response)| {
+            let pointer = pointer::join(&pointer::join(&pointer, "responses"), status);
+            let schema = response
+                .content
+                .as_ref()
+                .map(|content| {
+                    parse_content_to_type(content, ctx, &pointer::join(&pointer, "content"))
+                })
+                .transpose()?
+                .flatten()
+                .map(|(_, ty)| ty);
+            Ok(Response {
+                status: status.clone(),
+                schema,
+                docs: Documentation {
+                    description: response.description.clone(),
+                    ..Documentation::default()
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Operation {
+        name: if webhook {
+            naming::identifier(path)
+        } else {
+            naming::operation_name(operation.operation_id.as_deref(), method, path)
+        },
+        method: method.to_string(),
+        path: path.clone(),
+        docs: Documentation {
+            title: operation.summary.clone(),
+            description: operation.description.clone(),
+            deprecated: operation.deprecated == Some(true),
+            ..Documentation::default()
+        },
+        parameters,
+        request_body,
+        responses,
+        webhook,
+    })
 }
 
-pub fn use_spec3(spec: &Spec3) -> Vec<(String, JavaScriptType)> {
+fn version(spec: &Spec3) -> Version {
+    if spec.openapi.starts_with("3.1") {
+        Version::V3_1
+    } else {
+        Version::V3_0
+    }
+}
+
+/// The type of every schema of `components`, named after `names`.
+pub fn use_spec3(
+    spec: &Spec3,
+    names: &IndexMap<String, String>,
+    formats: &FormatMappings,
+    brands: bool,
+) -> Result<Vec<Definition>, Error> {
     if let Some(components) = spec.components.as_ref() {
         if let Some(schemas) = components.schemas.as_ref() {
-            schemas.iter().map(parse_schema).collect()
+            let ctx = Context {
+                version: version(spec),
+                schemas,
+                names,
+                formats,
+                brands,
+            };
+            schemas
+                .iter()
+                .map(|schema| parse_schema(schema, &ctx))
+                .collect()
         } else {
-            vec![]
+            Ok(vec![])
         }
     } else {
-        vec![]
+        Ok(vec![])
     }
 }
 
-const INVALID_KEY_TYPE_ERROR : &str = "Although YAML technically support having non-string keys. Only strings are valid keys in a JavaScript object.";
-
-fn parse_json_value_to_javascript_type(v: &serde_yaml::Value) -> JavaScriptValue {
-    match v {
-        serde_yaml::Value::Null => JavaScriptValue::Null,
-        serde_yaml::Value::Bool(b) => JavaScriptValue::from(b),
-        serde_yaml::Value::Number(n) => JavaScriptValue::from(n.as_f64().unwrap()),
-        serde_yaml::Value::String(s) => JavaScriptValue::from(s.as_str()),
-        serde_yaml::Value::Sequence(v) => {
-            JavaScriptValue::Array(v.iter().map(parse_json_value_to_javascript_type).collect())
-        }
-        serde_yaml::Value::Mapping(o) => JavaScriptValue::Object(
-            o.iter()
-                .filter_map(|(k, v)| match k {
-                    serde_yaml::Value::String(s) => Some((s, v)),
-                    _ => {
-                        eprintln!("error:\n{}value:\n{:#?}", INVALID_KEY_TYPE_ERROR, k);
-                        None
-                    }
-                })
-                .map(|(name, value)| (name.clone(), parse_json_value_to_javascript_type(value)))
-                .collect(),
-        ),
-    }
+/// Every operation of `paths`, followed by the ones of `webhooks`,
+/// referring to the schemas of `components` by their names in `names`.
+pub fn use_spec3_paths(
+    spec: &Spec3,
+    names: &IndexMap<String, String>,
+    formats: &FormatMappings,
+    brands: bool,
+) -> Result<Vec<Operation>, Error> {
+    let empty = BTreeMap::new();
+    let ctx = Context {
+        version: version(spec),
+        schemas: spec
+            .components
+            .as_ref()
+            .and_then(|c| c.schemas.as_ref())
+            .unwrap_or(&empty),
+        names,
+        formats,
+        brands,
+    };
+    let paths = spec.path_items(false)?;
+    let webhooks = spec.path_items(true)?;
+    paths
+        .iter()
+        .map(|item| (item, false))
+        .chain(webhooks.iter().map(|item| (item, true)))
+        .flat_map(|(item, webhook)| {
+            item.1
+                .operations()
+                .into_iter()
+                .map(move |operation| (item, operation, webhook))
+        })
+        .map(|(item, operation, webhook)| parse_operation(item, operation, webhook, &ctx))
+        .collect()
 }
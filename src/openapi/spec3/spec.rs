@@ -1,8 +1,12 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::collections::BTreeMap;
 
+/// A reference without any siblings.
+/// OpenAPI 3.1 allows a `$ref` to have sibling keywords, those are parsed as a [`Schema`] instead.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct Ref {
     #[serde(rename = "$ref")]
     pub ref_path: String,
@@ -23,11 +27,189 @@ pub enum BooleanObjectOrReference<T> {
     Ref(Ref),
 }
 
+/// The `type` of a schema.
+/// OpenAPI 3.1 allows multiple types, e.g. `["string", "null"]`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum SchemaType {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl SchemaType {
+    pub fn types(&self) -> Vec<&str> {
+        match self {
+            SchemaType::Single(t) => vec![t.as_str()],
+            SchemaType::Multiple(t) => t.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Spec3 {
     pub openapi: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Components>,
+    /// OpenAPI 3.1 only, path items by the name of the webhook.
+    /// Every reference to a parameter, request body or response is inlined.
+    /// Kept as is until the operations are generated, see [`Spec3::path_items`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhooks: Option<Value>,
+    /// Every reference to a parameter, request body or response is inlined.
+    /// Kept as is until the operations are generated, see [`Spec3::path_items`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Value>,
+}
+
+impl Spec3 {
+    /// The path items of `paths`, or of `webhooks` if `webhooks`,
+    /// so that they only have to be valid when the operations are generated.
+    pub fn path_items(
+        &self,
+        webhooks: bool,
+    ) -> Result<IndexMap<String, PathItem>, serde_yaml::Error> {
+        match if webhooks {
+            &self.webhooks
+        } else {
+            &self.paths
+        } {
+            Some(items) => serde_yaml::from_value(items.clone()),
+            None => Ok(IndexMap::new()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PathItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub put: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Operation>,
+    /// Parameters shared by all the operations of the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Parameter>>,
+}
+
+impl PathItem {
+    /// The operations of the path with their method.
+    pub fn operations(&self) -> Vec<(&'static str, &Operation)> {
+        vec![
+            ("get", &self.get),
+            ("put", &self.put),
+            ("post", &self.post),
+            ("delete", &self.delete),
+            ("options", &self.options),
+            ("head", &self.head),
+            ("patch", &self.patch),
+            ("trace", &self.trace),
+        ]
+        .into_iter()
+        .filter_map(|(method, operation)| operation.as_ref().map(|o| (method, o)))
+        .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Operation {
+    #[serde(rename = "operationId", skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<Parameter>>,
+    #[serde(rename = "requestBody", skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<RequestBody>,
+    /// Responses by status code, which may be written as a number.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_responses",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub responses: Option<IndexMap<String, Response>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    /// One of `path`, `query`, `header` or `cookie`.
+    #[serde(rename = "in")]
+    pub location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    /// Defaults to `form` for query and cookie parameters, `simple` for the others.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// Defaults to `true` for the `form` style, `false` for the others.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<ObjectOrReference<Schema>>,
+    /// Used instead of `schema` for complex parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<IndexMap<String, MediaType>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct RequestBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub content: IndexMap<String, MediaType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<IndexMap<String, MediaType>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MediaType {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<ObjectOrReference<Schema>>,
+}
+
+fn deserialize_responses<'de, D>(
+    deserializer: D,
+) -> Result<Option<IndexMap<String, Response>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<IndexMap<Value, Response>>::deserialize(deserializer)?
+        .map(|responses| {
+            responses
+                .into_iter()
+                .map(|(status, response)| match status {
+                    Value::Number(n) => Ok((n.to_string(), response)),
+                    Value::String(s) => Ok((s, response)),
+                    _ => Err(serde::de::Error::custom("invalid status code")),
+                })
+                .collect()
+        })
+        .transpose()
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -37,24 +219,74 @@ pub struct Components {
     pub schemas: Option<BTreeMap<String, ObjectOrReference<Schema>>>,
 }
 
+/// Tells which of the schemas of a `oneOf`, `anyOf` or of the schemas extending this one through `allOf`
+/// a value is, based on the value of one of its properties.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Discriminator {
+    #[serde(rename = "propertyName")]
+    pub property_name: String,
+    /// Maps the values of the property to the name of, or a reference to, a schema.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<IndexMap<String, String>>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Schema {
+    /// A reference with sibling keywords, only valid since OpenAPI 3.1.
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
+    pub ref_path: Option<String>,
     /// Properties.
     /// The following properties are taken directly from the [JSON Schema](https://tools.ietf.org/html/draft-wright-json-schema-00) definition and follow the same specification.
     /// TODO(hbina): Extend support to all of this.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(rename = "multipleOf", skip_serializing_if = "Option::is_none")]
-    pub multiple_of: Option<u32>,
+    pub multiple_of: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    /// A boolean modifying `minimum` in OpenAPI 3.0, a number on its own since OpenAPI 3.1.
+    #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_minimum: Option<Value>,
+    /// A boolean modifying `maximum` in OpenAPI 3.0, a number on its own since OpenAPI 3.1.
+    #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_maximum: Option<Value>,
+    #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u64>,
+    #[serde(rename = "maxLength", skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(rename = "maxItems", skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(rename = "uniqueItems", skip_serializing_if = "Option::is_none")]
+    pub unique_items: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
     #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
     pub enum_values: Option<Vec<Value>>,
+    /// Names of the `enum` values, a vendor extension used by various generators.
+    #[serde(rename = "x-enum-varnames", skip_serializing_if = "Option::is_none")]
+    pub x_enum_varnames: Option<Vec<String>>,
+    /// Same as `x-enum-varnames`.
+    #[serde(rename = "x-enumNames", skip_serializing_if = "Option::is_none")]
+    pub x_enum_names: Option<Vec<String>>,
+    /// The name of the nominal type the values are generated as when branded types are enabled.
+    #[serde(rename = "x-brand", skip_serializing_if = "Option::is_none")]
+    pub x_brand: Option<String>,
     /// OpenAPI Specific Properties.
     /// The following properties are taken from the JSON Schema definition but their definitions were adjusted to the OpenAPI Specification.
     // TODO(hbina): Extend support to all of this.
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub schema_type: Option<String>,
+    pub schema_type: Option<SchemaType>,
+    #[serde(rename = "const", skip_serializing_if = "Option::is_none")]
+    pub const_value: Option<Value>,
+    /// OpenAPI 3.0 only, 3.1 uses `type: [..., "null"]` instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
     // Inline or referenced schema MUST be of a [Schema Object](#schemaObject) and not a standard
     // JSON Schema.
     // [oneOf-anyOf-](https://swagger.io/docs/specification/data-models/oneof-anyof-allof-not/#oneof)
@@ -65,11 +297,16 @@ pub struct Schema {
     #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
     pub any_of: Option<Vec<ObjectOrReference<Schema>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub not: Option<Vec<ObjectOrReference<Schema>>>,
+    pub not: Option<Box<ObjectOrReference<Schema>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub items: Option<Box<ObjectOrReference<Schema>>>,
+    pub discriminator: Option<Discriminator>,
+    /// Since OpenAPI 3.1, `items: false` forbids elements after the `prefixItems`.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub properties: Option<BTreeMap<String, ObjectOrReference<Schema>>>,
+    pub items: Option<BooleanObjectOrReference<Box<Schema>>>,
+    #[serde(rename = "prefixItems", skip_serializing_if = "Option::is_none")]
+    pub prefix_items: Option<Vec<ObjectOrReference<Schema>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<IndexMap<String, ObjectOrReference<Schema>>>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         rename = "additionalProperties"
@@ -81,4 +318,18 @@ pub struct Schema {
     pub format: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    #[serde(rename = "readOnly", skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    #[serde(rename = "writeOnly", skip_serializing_if = "Option::is_none")]
+    pub write_only: Option<bool>,
+    /// OpenAPI 3.0 only, 3.1 uses `examples` instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<Vec<Value>>,
+    /// Every definition is resolved into `components`, this is only kept for completeness.
+    #[serde(rename = "$defs", skip_serializing_if = "Option::is_none")]
+    pub defs: Option<BTreeMap<String, ObjectOrReference<Schema>>>,
 }